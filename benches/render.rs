@@ -0,0 +1,140 @@
+//! Throughput of [render_to_buffer] with an increasing number of threads
+//!
+//! Run with `cargo bench --features bench`. Since every thread writes to its own rows of the buffer,
+//! the throughput should grow almost linearly with the number of cores.
+//! [render_to_buffer_locked], which writes every pixel through a shared lock,
+//! is measured first as the baseline of the gain.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use raytrascii::camera::Camera;
+use raytrascii::color::Color;
+use raytrascii::render::{render_to_buffer, render_to_buffer_locked, FrameBuffer, RenderSettings};
+use raytrascii::scene::hittable::Sphere;
+use raytrascii::scene::material::{Dielectric, Lambertian, Metal};
+use raytrascii::scene::{Scene, SceneBackground};
+
+const WIDTH: usize = 160;
+const HEIGHT: usize = 90;
+const SAMPLES_PER_PIXEL: usize = 8;
+const MAX_DEPTH: usize = 15;
+
+/// Minimum time that is spent rendering for each number of threads
+const MEASUREMENT_TIME: Duration = Duration::from_secs(3);
+
+/// Function that renders a frame
+type Render = fn(&Scene, &Camera, usize, usize, &RenderSettings) -> FrameBuffer;
+
+fn main() {
+    let scene = bench_scene();
+    let cam = Camera::new(
+        (0.0, 1.5, 4.0).into(),
+        (0.0, 0.0, -1.0).into(),
+        (0.0, 1.0, 0.0).into(),
+        60.0,
+    );
+    let settings = RenderSettings::new(MAX_DEPTH, SAMPLES_PER_PIXEL);
+
+    let max_threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let thread_counts: Vec<usize> = (0..)
+        .map(|exp| 1 << exp)
+        .take_while(|&n| n < max_threads)
+        .chain([max_threads])
+        .collect();
+
+    println!(
+        "rendering {}x{} pixels with {} samples per pixel",
+        WIDTH, HEIGHT, SAMPLES_PER_PIXEL
+    );
+
+    let groups: [(&str, Render); 2] = [
+        ("shared lock (baseline)", render_to_buffer_locked),
+        ("row buffer", render_to_buffer),
+    ];
+    let mut baseline: Vec<f64> = Vec::new();
+
+    for (name, render) in groups {
+        println!("\n{}", name);
+
+        let mut single_thread_throughput = None;
+
+        for (i, &threads) in thread_counts.iter().enumerate() {
+            let throughput = measure(render, threads, &scene, &cam, &settings);
+            let speedup = throughput / *single_thread_throughput.get_or_insert(throughput);
+
+            // the first group is the baseline of the others
+            let gain = match baseline.get(i) {
+                Some(base) => throughput / base,
+                None => {
+                    baseline.push(throughput);
+                    1.0
+                }
+            };
+
+            println!(
+                "{:>3} threads: {:>8.3} Msamples/s ({:>5.2}x threads, {:>5.2}x baseline)",
+                threads, throughput, speedup, gain
+            );
+        }
+    }
+}
+
+/// Return the throughput of `render` on `threads` threads in million samples per second
+fn measure(
+    render: Render,
+    threads: usize,
+    scene: &Scene,
+    cam: &Camera,
+    settings: &RenderSettings,
+) -> f64 {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .expect("Could not build thread pool");
+
+    let (frames, elapsed) = pool.install(|| {
+        let start = Instant::now();
+        let mut frames = 0;
+
+        while frames == 0 || start.elapsed() < MEASUREMENT_TIME {
+            render(scene, cam, WIDTH, HEIGHT, settings);
+            frames += 1;
+        }
+
+        (frames, start.elapsed())
+    });
+
+    let samples = (frames * WIDTH * HEIGHT * SAMPLES_PER_PIXEL) as f64;
+    samples / elapsed.as_secs_f64() / 1e6
+}
+
+/// Grid of spheres of every material on a large ground sphere
+fn bench_scene() -> Scene {
+    let mut builder = Scene::builder(SceneBackground::VerticalGradient {
+        top: Color::new(0.5, 0.7, 1.0),
+        bottom: Color::white(),
+    })
+    .add_object(Box::new(Sphere::new(
+        (0.0, -1000.5, -1.0).into(),
+        1000.0,
+        Lambertian::new(Color::new(0.5, 0.5, 0.5)).boxed(),
+    )));
+
+    for x in -3_i32..=3 {
+        for z in -4..=0 {
+            let center = (x as f64, 0.0, z as f64 - 1.0).into();
+            let color = Color::new(0.2 + 0.1 * (x + 3) as f64, 0.3, 0.2 + 0.15 * (-z) as f64);
+
+            let sphere = match (x + z).rem_euclid(3) {
+                0 => Sphere::new(center, 0.4, Lambertian::new(color).boxed()),
+                1 => Sphere::new(center, 0.4, Metal::new(color, 0.2).boxed()),
+                _ => Sphere::new(center, 0.4, Dielectric::new(1.5).boxed()),
+            };
+
+            builder = builder.add_object(Box::new(sphere));
+        }
+    }
+
+    builder.use_bvh(true).build()
+}
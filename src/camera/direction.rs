@@ -16,4 +16,15 @@ pub enum MoveDirection {
 pub enum RotationDirection {
     Left,
     Right,
+    Up,
+    Down,
+    RollLeft,
+    RollRight,
+}
+
+/// Directions to zoom in
+#[derive(Debug)]
+pub enum ZoomDirection {
+    In,
+    Out,
 }
@@ -1,21 +1,42 @@
 //! The virtual camera
 
+use std::f64::consts::PI;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
 use crate::lalg::{OrthNormBasis3, Point3, Vec3};
+use crate::ray::Ray;
+use crate::scene::hittable::Hittable;
+use crate::scene::Scene;
 use crate::utils;
 
-use direction::{MoveDirection, RotationDirection};
-use view::CameraView;
+use direction::{MoveDirection, RotationDirection, ZoomDirection};
+pub(crate) use view::CameraView;
 
 pub mod direction;
 mod view;
 
+/// Smallest angle in degrees between the view direction and the "view up" vector
+const MIN_PITCH_ANGLE: f64 = 1.0;
+
+/// Smallest vertical field of view in degrees
+const MIN_VFOV: f64 = 1.0;
+
+/// Largest vertical field of view in degrees
+const MAX_VFOV: f64 = 179.0;
+
 /// Virtual movable camera
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Camera {
     pos: Point3,
     lookat: Point3,
     vup: Vec3,
     vfov: f64,
+    aperture: f64,
+    focus_dist: f64,
+    shutter_open: f64,
+    shutter_close: f64,
 }
 
 impl Default for Camera {
@@ -25,11 +46,66 @@ impl Default for Camera {
             lookat: Point3::new(0.0, 0.0, -1.0),
             vup: Vec3::new(0.0, 1.0, 0.0),
             vfov: 90.0,
+            aperture: 0.0,
+            focus_dist: 1.0,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
         }
     }
 }
 
 impl Camera {
+    /// Default shorthand constructor (for a pinhole camera without defocus blur)
+    pub fn new(pos: Point3, lookat: Point3, vup: Vec3, vfov: f64) -> Self {
+        Camera {
+            pos,
+            lookat,
+            vup,
+            vfov,
+            ..Camera::default()
+        }
+    }
+
+    /// Return the position
+    pub fn pos(&self) -> Point3 {
+        self.pos
+    }
+
+    /// Return the point the camera looks at
+    pub fn lookat(&self) -> Point3 {
+        self.lookat
+    }
+
+    /// Return the "view up" vector
+    pub fn vup(&self) -> Vec3 {
+        self.vup
+    }
+
+    /// Return the vertical field of view in degrees
+    pub fn vfov(&self) -> f64 {
+        self.vfov
+    }
+
+    /// Return the diameter of the lens
+    pub fn aperture(&self) -> f64 {
+        self.aperture
+    }
+
+    /// Return the distance to the plane that is in perfect focus
+    pub fn focus_dist(&self) -> f64 {
+        self.focus_dist
+    }
+
+    /// Return the point in time at which the shutter opens
+    pub fn shutter_open(&self) -> f64 {
+        self.shutter_open
+    }
+
+    /// Return the point in time at which the shutter closes
+    pub fn shutter_close(&self) -> f64 {
+        self.shutter_close
+    }
+
     /// Get a static view on the scene
     pub(crate) fn get_view(&self, aspect_ratio: f64) -> CameraView {
         let theta = utils::degrees_to_radians(self.vfov);
@@ -40,15 +116,65 @@ impl Camera {
         let origin = self.pos;
         let orientation = OrthNormBasis3::orientation(self.pos, self.lookat, self.vup);
 
-        let horiz = viewport_width * orientation.u;
-        let vert = viewport_height * orientation.v;
-        let lower_left_corner = origin - (horiz / 2.0) - (vert / 2.0) - orientation.w;
+        let horiz = self.focus_dist * viewport_width * orientation.u;
+        let vert = self.focus_dist * viewport_height * orientation.v;
+        let lower_left_corner =
+            origin - (horiz / 2.0) - (vert / 2.0) - (self.focus_dist * orientation.w);
 
         CameraView {
             origin,
             lower_left_corner,
             horiz,
             vert,
+            u: orientation.u,
+            v: orientation.v,
+            lens_radius: self.aperture / 2.0,
+            time0: self.shutter_open,
+            time1: self.shutter_close,
+        }
+    }
+}
+
+/* FOCUS */
+impl Camera {
+    /// Set the diameter of the lens (`0` disables defocus blur)
+    pub fn set_aperture(&mut self, aperture: f64) {
+        self.aperture = aperture.max(0.0);
+    }
+
+    /// Set the distance to the plane that is in perfect focus
+    pub fn set_focus_dist(&mut self, focus_dist: f64) {
+        if focus_dist > 0.0 {
+            self.focus_dist = focus_dist;
+        }
+    }
+
+    /// Focus on the object in the center of the view
+    ///
+    /// Return the new focus distance or `None` if there is no object to focus on.
+    /// Moving objects are focused where they are in the middle of the shutter interval.
+    pub fn autofocus(&mut self, scene: &Scene) -> Option<f64> {
+        let time = (self.shutter_open + self.shutter_close) / 2.0;
+        let ray = Ray::with_time(self.pos, (self.lookat - self.pos).unit_vec(), time);
+        // a fixed seed focuses on the same point in participating media every time
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let rec = scene.objects.hit(&ray, 0.001, f64::INFINITY, &mut rng)?;
+
+        self.set_focus_dist(rec.t);
+        Some(self.focus_dist)
+    }
+}
+
+/* MOTION BLUR */
+impl Camera {
+    /// Set the points in time at which the shutter opens and closes
+    ///
+    /// Rays are sent at random times in between, so objects that move during
+    /// this interval are blurred. Equal times disable motion blur.
+    pub fn set_shutter(&mut self, open: f64, close: f64) {
+        if open <= close {
+            self.shutter_open = open;
+            self.shutter_close = close;
         }
     }
 }
@@ -57,13 +183,10 @@ impl Camera {
 impl Camera {
     /// Move the camera to absolute coordinates
     pub fn move_absolute(&mut self, x: f64, y: f64, z: f64) {
-        self.pos.x = x;
-        self.pos.y = y;
-        self.pos.z = z;
+        let look = self.lookat - self.pos;
 
-        self.lookat.x = x;
-        self.lookat.y = y;
-        self.lookat.z = z;
+        self.pos = Point3::new(x, y, z);
+        self.lookat = self.pos + look;
     }
 
     /// Move the camera in a relative direction
@@ -80,12 +203,12 @@ impl Camera {
                 self.lookat -= look_dir * step;
             }
             MoveDirection::Left => {
-                let dir = self.vup.cross(look_dir);
+                let dir = self.vup.cross(look_dir).unit_vec();
                 self.pos += dir * step;
                 self.lookat += dir * step;
             }
             MoveDirection::Right => {
-                let dir = self.vup.cross(look_dir);
+                let dir = self.vup.cross(look_dir).unit_vec();
                 self.pos -= dir * step;
                 self.lookat -= dir * step;
             }
@@ -100,19 +223,202 @@ impl Camera {
         }
     }
 
-    /// Rotate the camera in a relative direction
-    pub fn rotate(&mut self, dir: RotationDirection, step: f64) {
-        let look_dir: Vec3 = (self.lookat - self.pos).unit_vec();
+    /// Rotate the camera in a relative direction by an angle in degrees
+    ///
+    /// Pitching is clamped so that the camera never looks exactly along its "view up" vector.
+    pub fn rotate(&mut self, dir: RotationDirection, angle: f64) {
+        let look = self.lookat - self.pos;
+        let dist = look.len();
+        let look_dir = look / dist;
+        let up = self.vup.unit_vec();
+        let angle = utils::degrees_to_radians(angle);
 
         match dir {
             RotationDirection::Left => {
-                let dir = self.vup.cross(look_dir);
-                self.lookat += dir * step;
+                self.lookat = self.pos + look_dir.rotate_around(up, angle) * dist;
             }
             RotationDirection::Right => {
-                let dir = self.vup.cross(look_dir);
-                self.lookat -= dir * step;
+                self.lookat = self.pos + look_dir.rotate_around(up, -angle) * dist;
+            }
+            RotationDirection::Up | RotationDirection::Down => {
+                let min_theta = utils::degrees_to_radians(MIN_PITCH_ANGLE);
+                let theta = look_dir.dot(up).clamp(-1.0, 1.0).acos();
+
+                let theta = match dir {
+                    RotationDirection::Up => theta - angle,
+                    _ => theta + angle,
+                };
+                let theta = theta.clamp(min_theta, PI - min_theta);
+
+                let horiz = look_dir - (up * look_dir.dot(up));
+                if horiz.near_zero() {
+                    return;
+                }
+
+                let look_dir = (up * theta.cos()) + (horiz.unit_vec() * theta.sin());
+                self.lookat = self.pos + look_dir * dist;
+            }
+            RotationDirection::RollLeft => {
+                self.vup = self.vup.rotate_around(look_dir, -angle);
             }
+            RotationDirection::RollRight => {
+                self.vup = self.vup.rotate_around(look_dir, angle);
+            }
+        }
+    }
+
+    /// Zoom in or out by changing the vertical field of view by an angle in degrees
+    pub fn zoom(&mut self, dir: ZoomDirection, angle: f64) {
+        match dir {
+            ZoomDirection::In => self.set_vfov(self.vfov - angle),
+            ZoomDirection::Out => self.set_vfov(self.vfov + angle),
         }
     }
+
+    /// Set the vertical field of view in degrees
+    ///
+    /// The value is clamped to a range that yields a valid view.
+    pub fn set_vfov(&mut self, vfov: f64) {
+        self.vfov = vfov.clamp(MIN_VFOV, MAX_VFOV);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::direction::{MoveDirection, RotationDirection, ZoomDirection};
+    use super::{Camera, MAX_VFOV, MIN_PITCH_ANGLE, MIN_VFOV};
+    use crate::color::Color;
+    use crate::lalg::{Point3, Vec3};
+    use crate::scene::hittable::{MovingSphere, Sphere};
+    use crate::scene::material::DefaultMaterial;
+    use crate::scene::{Scene, SceneBackground};
+
+    const EPS: f64 = 1e-9;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).len() < EPS, "{:?} != {:?}", a, b);
+    }
+
+    fn look_dir(cam: &Camera) -> Vec3 {
+        (cam.lookat() - cam.pos()).unit_vec()
+    }
+
+    fn empty_scene() -> Scene {
+        Scene::builder(SceneBackground::Solid(Color::white())).build()
+    }
+
+    #[test]
+    fn move_relative() {
+        let mut cam = Camera::default();
+
+        cam.move_relative(MoveDirection::Forward, 2.0);
+        assert_close(cam.pos(), Point3::new(0.0, 0.0, -2.0));
+        assert_close(cam.lookat(), Point3::new(0.0, 0.0, -3.0));
+
+        cam.move_relative(MoveDirection::Right, 1.0);
+        cam.move_relative(MoveDirection::Up, 0.5);
+        assert_close(cam.pos(), Point3::new(1.0, 0.5, -2.0));
+        assert_close(look_dir(&cam), Vec3::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn turn_left_and_right() {
+        let mut cam = Camera::default();
+
+        cam.rotate(RotationDirection::Left, 90.0);
+        assert_close(look_dir(&cam), Vec3::new(-1.0, 0.0, 0.0));
+        assert!(((cam.lookat() - cam.pos()).len() - 1.0).abs() < EPS);
+
+        cam.rotate(RotationDirection::Right, 180.0);
+        assert_close(look_dir(&cam), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(cam.vup(), Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn pitch_is_clamped() {
+        let mut cam = Camera::default();
+
+        cam.rotate(RotationDirection::Up, 45.0);
+        assert_close(look_dir(&cam), Vec3::new(0.0, 1.0, -1.0).unit_vec());
+
+        cam.rotate(RotationDirection::Up, 180.0);
+        let angle = look_dir(&cam).dot(cam.vup()).acos().to_degrees();
+        assert!((angle - MIN_PITCH_ANGLE).abs() < 1e-6, "{}", angle);
+
+        cam.rotate(RotationDirection::Down, 360.0);
+        let angle = look_dir(&cam).dot(cam.vup()).acos().to_degrees();
+        assert!(
+            (angle - (180.0 - MIN_PITCH_ANGLE)).abs() < 1e-6,
+            "{}",
+            angle
+        );
+
+        // the camera keeps facing forward
+        assert!(look_dir(&cam).z < 0.0);
+    }
+
+    #[test]
+    fn roll() {
+        let mut cam = Camera::default();
+
+        cam.rotate(RotationDirection::RollRight, 90.0);
+        assert_close(cam.vup(), Vec3::new(1.0, 0.0, 0.0));
+
+        cam.rotate(RotationDirection::RollLeft, 90.0);
+        assert_close(cam.vup(), Vec3::new(0.0, 1.0, 0.0));
+        assert_close(look_dir(&cam), Vec3::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn zoom_is_clamped() {
+        let mut cam = Camera::default();
+
+        cam.zoom(ZoomDirection::In, 30.0);
+        assert_eq!(cam.vfov(), 60.0);
+
+        cam.zoom(ZoomDirection::In, 100.0);
+        assert_eq!(cam.vfov(), MIN_VFOV);
+
+        cam.zoom(ZoomDirection::Out, 500.0);
+        assert_eq!(cam.vfov(), MAX_VFOV);
+    }
+
+    #[test]
+    fn autofocus() {
+        let scene = Scene::builder(SceneBackground::Solid(Color::white()))
+            .add_object(Box::new(Sphere::new(
+                Point3::new(0.0, 0.0, -3.0),
+                1.0,
+                DefaultMaterial.boxed(),
+            )))
+            .build();
+        let mut cam = Camera::default();
+
+        assert_eq!(cam.autofocus(&scene), Some(2.0));
+        assert_eq!(cam.focus_dist(), 2.0);
+
+        // nothing to focus on keeps the focus
+        assert_eq!(cam.autofocus(&empty_scene()), None);
+        assert_eq!(cam.focus_dist(), 2.0);
+    }
+
+    #[test]
+    fn autofocus_during_shutter_interval() {
+        // the sphere moves away from z = -3 at time 0 to z = -7 at time 1
+        let sphere = MovingSphere::new(
+            (Point3::new(0.0, 0.0, -3.0), Point3::new(0.0, 0.0, -7.0)),
+            (0.0, 1.0),
+            1.0,
+            DefaultMaterial.boxed(),
+        );
+        let scene = Scene::builder(SceneBackground::Solid(Color::white()))
+            .add_object(sphere.boxed())
+            .build();
+
+        let mut cam = Camera::default();
+        cam.set_shutter(0.5, 1.0);
+
+        let focus_dist = cam.autofocus(&scene).unwrap();
+        assert!((focus_dist - 5.0).abs() < EPS, "{}", focus_dist);
+    }
 }
@@ -1,3 +1,5 @@
+use rand::Rng;
+
 use crate::lalg::{Point3, Vec3};
 use crate::ray::Ray;
 
@@ -8,14 +10,41 @@ pub(crate) struct CameraView {
     pub(crate) lower_left_corner: Point3,
     pub(crate) horiz: Vec3,
     pub(crate) vert: Vec3,
+    pub(crate) u: Vec3,
+    pub(crate) v: Vec3,
+    pub(crate) lens_radius: f64,
+    pub(crate) time0: f64,
+    pub(crate) time1: f64,
 }
 
 impl CameraView {
     /// Return the ray located at a given point in the viewport
-    pub(crate) fn get_ray(&self, s: f64, t: f64) -> Ray {
+    pub(crate) fn get_ray(&self, s: f64, t: f64, rng: &mut impl Rng) -> Ray {
+        let offset = if self.lens_radius > 0.0 {
+            let rd = self.lens_radius * Vec3::random_in_unit_disk(rng);
+            (self.u * rd.x) + (self.v * rd.y)
+        } else {
+            Vec3::origin()
+        };
+
+        let time = if self.time1 > self.time0 {
+            rng.gen_range(self.time0..self.time1)
+        } else {
+            self.time0
+        };
+
         Ray {
-            origin: self.origin,
-            dir: self.lower_left_corner + (s * self.horiz) + (t * self.vert) - self.origin,
+            origin: self.origin + offset,
+            dir: self.lower_left_corner + (s * self.horiz) + (t * self.vert) - self.origin - offset,
+            time,
         }
     }
+
+    /// Return the ray through the center of the lens at the time the shutter opens
+    ///
+    /// Unlike [get_ray][CameraView::get_ray] this is deterministic.
+    pub(crate) fn get_pinhole_ray(&self, s: f64, t: f64) -> Ray {
+        let dir = self.lower_left_corner + (s * self.horiz) + (t * self.vert) - self.origin;
+        Ray::with_time(self.origin, dir, self.time0)
+    }
 }
@@ -0,0 +1,291 @@
+//! Command-line interface of the raytrascii binary
+
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+
+use raytrascii::render::{Background, ColorDepth, Palette, RenderDimensions, RenderMode};
+
+/// ASCII ray tracing for the terminal
+///
+/// Navigate with the arrow keys and hold Ctrl to turn or move vertically.
+/// Look around with W/A/S/D, roll with Q/E, zoom with +/-,
+/// press F to focus on the object in the center and press Esc to quit.
+#[derive(Debug, Parser)]
+#[command(version)]
+pub struct Args {
+    /// Scene description file to render (uses a built-in scene if omitted)
+    pub scene: Option<PathBuf>,
+
+    /// What the characters of the output express
+    #[arg(short, long, value_enum, default_value_t = Mode::ColorAndBrightness)]
+    pub mode: Mode,
+
+    /// Built-in characters that express brightness
+    #[arg(short, long, value_enum, default_value_t = PalettePreset::Ascii)]
+    pub palette: PalettePreset,
+
+    /// Custom characters that express brightness, ordered from sparse to dense (e.g. ` .oO@`)
+    #[arg(long, value_name = "CHARS", value_parser = parse_ramp, conflicts_with = "palette")]
+    pub ramp: Option<Palette>,
+
+    /// Background color of the terminal (bright pixels get dense characters on dark backgrounds)
+    #[arg(long, value_enum, default_value_t = TermBackground::Light)]
+    pub background: TermBackground,
+
+    /// Colors the terminal supports (detected from `COLORTERM` and `TERM` by default)
+    #[arg(long, value_enum, default_value_t = Colors::Auto)]
+    pub colors: Colors,
+
+    /// Dither colors that the terminal cannot display
+    #[arg(long)]
+    pub dither: bool,
+
+    /// Render at a fixed size of COLSxROWS characters (e.g. `120x40`)
+    #[arg(long, value_name = "COLSxROWS", value_parser = parse_size, conflicts_with = "size_offset")]
+    pub size: Option<(u16, u16)>,
+
+    /// Render at the terminal size changed by COLS,ROWS characters (e.g. `-2,-1`)
+    #[arg(long, value_name = "COLS,ROWS", value_parser = parse_offset, allow_hyphen_values = true)]
+    pub size_offset: Option<(i32, i32)>,
+
+    /// Number of rays that are traced per pixel and frame
+    #[arg(short = 'n', long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    pub samples: u32,
+
+    /// Number of samples per pixel after which a still view stops being refined
+    #[arg(long, default_value_t = 1000, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_samples: u32,
+
+    /// Maximum number of times a ray bounces off objects
+    #[arg(short, long, default_value_t = 15, value_parser = clap::value_parser!(u32).range(1..))]
+    pub depth: u32,
+
+    /// Seed of the random numbers (renders with the same seed and options are identical)
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Initial camera position as X,Y,Z
+    #[arg(long, value_name = "X,Y,Z", value_parser = parse_vec3, allow_hyphen_values = true)]
+    pub pos: Option<(f64, f64, f64)>,
+
+    /// Initial point the camera looks at as X,Y,Z
+    #[arg(long, value_name = "X,Y,Z", value_parser = parse_vec3, allow_hyphen_values = true)]
+    pub lookat: Option<(f64, f64, f64)>,
+
+    /// Initial "view up" vector of the camera as X,Y,Z
+    #[arg(long, value_name = "X,Y,Z", value_parser = parse_vec3, allow_hyphen_values = true)]
+    pub vup: Option<(f64, f64, f64)>,
+
+    /// Initial vertical field of view of the camera in degrees
+    #[arg(long, value_name = "DEGREES")]
+    pub vfov: Option<f64>,
+
+    /// Initial diameter of the camera lens (0 disables defocus blur)
+    #[arg(long)]
+    pub aperture: Option<f64>,
+
+    /// Initial distance of the camera to the plane in focus
+    #[arg(long)]
+    pub focus_dist: Option<f64>,
+
+    /// Print a single frame and exit instead of running interactively (colored only on a terminal)
+    #[arg(long)]
+    pub once: bool,
+
+    /// Render a single frame to an image file and exit (`.ppm`, or `.png` with the `png` feature)
+    #[arg(short, long, value_name = "FILE", conflicts_with = "once")]
+    pub output: Option<PathBuf>,
+
+    /// Size of the image file in pixels (e.g. `1280x720`)
+    #[arg(long, value_name = "WIDTHxHEIGHT", value_parser = parse_resolution, default_value = "640x360")]
+    pub resolution: (usize, usize),
+}
+
+impl Args {
+    /// Return the palette of the rendered output
+    pub fn palette(&self) -> Palette {
+        let palette = match &self.ramp {
+            Some(ramp) => ramp.clone(),
+            None => self.palette.into(),
+        };
+
+        palette.with_background(self.background.into())
+    }
+
+    /// Return the dimensions of the rendered output
+    pub fn dimensions(&self) -> RenderDimensions {
+        match (self.size, self.size_offset) {
+            (Some((cols, rows)), _) => RenderDimensions::ConcreteSize { cols, rows },
+            (None, Some((offset_cols, offset_rows))) => RenderDimensions::RelativeToTermSize {
+                offset_cols,
+                offset_rows,
+            },
+            (None, None) => RenderDimensions::TermSize,
+        }
+    }
+}
+
+/// Command-line representation of [RenderMode]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Mode {
+    /// Characters of different brightness without color
+    Brightness,
+    /// Colored characters of the same brightness
+    Color,
+    /// Colored characters of different brightness
+    ColorAndBrightness,
+    /// Colored half blocks with two pixels per character
+    HalfBlock,
+    /// Colored Braille patterns with 2x4 dots per character
+    Braille,
+    /// Colored characters of different brightness with outlines along edges
+    Edges,
+}
+
+impl From<Mode> for RenderMode {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Brightness => RenderMode::Brightness,
+            Mode::Color => RenderMode::Color,
+            Mode::ColorAndBrightness => RenderMode::ColorAndBrightness,
+            Mode::HalfBlock => RenderMode::HalfBlock,
+            Mode::Braille => RenderMode::Braille,
+            Mode::Edges => RenderMode::Edges,
+        }
+    }
+}
+
+/// Command-line representation of [ColorDepth]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Colors {
+    /// Detect the colors from the environment
+    Auto,
+    /// 24-bit RGB colors
+    Truecolor,
+    /// The xterm-256 palette
+    #[value(name = "256")]
+    Ansi256,
+    /// The 16 ANSI colors
+    #[value(name = "16")]
+    Ansi16,
+}
+
+impl From<Colors> for ColorDepth {
+    fn from(colors: Colors) -> Self {
+        match colors {
+            Colors::Auto => ColorDepth::detect(),
+            Colors::Truecolor => ColorDepth::TrueColor,
+            Colors::Ansi256 => ColorDepth::Ansi256,
+            Colors::Ansi16 => ColorDepth::Ansi16,
+        }
+    }
+}
+
+/// Command-line representation of the [Palette] presets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PalettePreset {
+    /// 69 ASCII characters with fine brightness steps
+    Ascii,
+    /// 10 ASCII characters with coarse brightness steps
+    Short,
+    /// Unicode block elements
+    Blocks,
+}
+
+impl From<PalettePreset> for Palette {
+    fn from(preset: PalettePreset) -> Self {
+        match preset {
+            PalettePreset::Ascii => Palette::ascii(),
+            PalettePreset::Short => Palette::short(),
+            PalettePreset::Blocks => Palette::blocks(),
+        }
+    }
+}
+
+/// Command-line representation of [Background]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TermBackground {
+    /// Dark pixels are drawn with dense characters
+    Light,
+    /// Bright pixels are drawn with dense characters
+    Dark,
+}
+
+impl From<TermBackground> for Background {
+    fn from(background: TermBackground) -> Self {
+        match background {
+            TermBackground::Light => Background::Light,
+            TermBackground::Dark => Background::Dark,
+        }
+    }
+}
+
+fn parse_ramp(s: &str) -> Result<Palette, String> {
+    Palette::new(s).ok_or_else(|| String::from("ramp must contain at least one character"))
+}
+
+fn parse_size(s: &str) -> Result<(u16, u16), String> {
+    let (cols, rows) = s
+        .split_once('x')
+        .ok_or_else(|| format!("expected COLSxROWS, found '{}'", s))?;
+
+    let cols: u16 = cols
+        .parse()
+        .map_err(|e| format!("invalid columns: {}", e))?;
+    let rows: u16 = rows.parse().map_err(|e| format!("invalid rows: {}", e))?;
+
+    if cols < 2 || rows < 2 {
+        return Err(String::from("size must be at least 2x2"));
+    }
+
+    Ok((cols, rows))
+}
+
+fn parse_resolution(s: &str) -> Result<(usize, usize), String> {
+    let (width, height) = s
+        .split_once('x')
+        .ok_or_else(|| format!("expected WIDTHxHEIGHT, found '{}'", s))?;
+
+    let width: usize = width.parse().map_err(|e| format!("invalid width: {}", e))?;
+    let height: usize = height
+        .parse()
+        .map_err(|e| format!("invalid height: {}", e))?;
+
+    if width < 2 || height < 2 {
+        return Err(String::from("resolution must be at least 2x2"));
+    }
+
+    Ok((width, height))
+}
+
+fn parse_offset(s: &str) -> Result<(i32, i32), String> {
+    let (cols, rows) = s
+        .split_once(',')
+        .ok_or_else(|| format!("expected COLS,ROWS, found '{}'", s))?;
+
+    let cols: i32 = cols
+        .parse()
+        .map_err(|e| format!("invalid columns: {}", e))?;
+    let rows: i32 = rows.parse().map_err(|e| format!("invalid rows: {}", e))?;
+
+    let max = u16::MAX as i32;
+    if !(-max..=max).contains(&cols) || !(-max..=max).contains(&rows) {
+        return Err(format!("offset must be between -{} and {}", max, max));
+    }
+
+    Ok((cols, rows))
+}
+
+fn parse_vec3(s: &str) -> Result<(f64, f64, f64), String> {
+    let parts = s
+        .split(',')
+        .map(|part| part.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("invalid number: {}", e))?;
+
+    match parts[..] {
+        [x, y, z] => Ok((x, y, z)),
+        _ => Err(format!("expected X,Y,Z, found '{}'", s)),
+    }
+}
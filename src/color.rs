@@ -52,9 +52,7 @@ impl Color {
     }
 
     /// Constructs a random color with values in `[0; 1]`
-    pub fn random() -> Self {
-        let mut rng = rand::thread_rng();
-
+    pub fn random(rng: &mut (impl Rng + ?Sized)) -> Self {
         Color {
             r: rng.gen(),
             g: rng.gen(),
@@ -0,0 +1,305 @@
+use std::ops;
+
+use super::{Point3, Vec3};
+use crate::utils;
+
+/// 4x4 matrix for affine transformations in homogeneous coordinates
+///
+/// The matrix is stored row by row. Points and vectors are treated as column vectors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4(pub [[f64; 4]; 4]);
+
+/* CONSTRUCTORS */
+impl Mat4 {
+    /// Constructs the identity matrix
+    pub fn identity() -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+
+        Mat4(m)
+    }
+
+    /// Constructs a translation by `offset`
+    pub fn translation(offset: Vec3) -> Self {
+        let mut m = Mat4::identity();
+        m.0[0][3] = offset.x;
+        m.0[1][3] = offset.y;
+        m.0[2][3] = offset.z;
+
+        m
+    }
+
+    /// Constructs a scaling by the factors of each axis
+    pub fn scaling(factors: Vec3) -> Self {
+        let mut m = Mat4::identity();
+        m.0[0][0] = factors.x;
+        m.0[1][1] = factors.y;
+        m.0[2][2] = factors.z;
+
+        m
+    }
+
+    /// Constructs a rotation around the x axis by `angle` degrees
+    pub fn rotation_x(angle: f64) -> Self {
+        let (sin, cos) = utils::degrees_to_radians(angle).sin_cos();
+
+        let mut m = Mat4::identity();
+        m.0[1][1] = cos;
+        m.0[1][2] = -sin;
+        m.0[2][1] = sin;
+        m.0[2][2] = cos;
+
+        m
+    }
+
+    /// Constructs a rotation around the y axis by `angle` degrees
+    pub fn rotation_y(angle: f64) -> Self {
+        let (sin, cos) = utils::degrees_to_radians(angle).sin_cos();
+
+        let mut m = Mat4::identity();
+        m.0[0][0] = cos;
+        m.0[0][2] = sin;
+        m.0[2][0] = -sin;
+        m.0[2][2] = cos;
+
+        m
+    }
+
+    /// Constructs a rotation around the z axis by `angle` degrees
+    pub fn rotation_z(angle: f64) -> Self {
+        let (sin, cos) = utils::degrees_to_radians(angle).sin_cos();
+
+        let mut m = Mat4::identity();
+        m.0[0][0] = cos;
+        m.0[0][1] = -sin;
+        m.0[1][0] = sin;
+        m.0[1][1] = cos;
+
+        m
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/* CONVERTERS */
+impl Mat4 {
+    /// Return the transposed matrix
+    pub fn transpose(self) -> Mat4 {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, x) in row.iter_mut().enumerate() {
+                *x = self.0[j][i];
+            }
+        }
+
+        Mat4(m)
+    }
+
+    /// Return the inverse matrix or `None` if the matrix is singular
+    pub fn inverse(self) -> Option<Mat4> {
+        // Gauss-Jordan elimination with partial pivoting
+        let mut a = self.0;
+        let mut inv = Mat4::identity().0;
+
+        for col in 0..4 {
+            let pivot = (col..4).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+            if a[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let p = a[col][col];
+            for j in 0..4 {
+                a[col][j] /= p;
+                inv[col][j] /= p;
+            }
+
+            for row in 0..4 {
+                if row == col {
+                    continue;
+                }
+
+                let factor = a[row][col];
+                for j in 0..4 {
+                    a[row][j] -= factor * a[col][j];
+                    inv[row][j] -= factor * inv[col][j];
+                }
+            }
+        }
+
+        Some(Mat4(inv))
+    }
+}
+
+/* TRANSFORMATIONS */
+impl Mat4 {
+    /// Transform a point (including translation)
+    pub fn transform_point(&self, p: Point3) -> Point3 {
+        let m = &self.0;
+
+        Point3::new(
+            m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3],
+            m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3],
+            m[2][0] * p.x + m[2][1] * p.y + m[2][2] * p.z + m[2][3],
+        )
+    }
+
+    /// Transform a direction vector (without translation)
+    pub fn transform_vec(&self, v: Vec3) -> Vec3 {
+        let m = &self.0;
+
+        Vec3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        )
+    }
+}
+
+/* ALGEBRAIC OPS */
+impl ops::Mul<Mat4> for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, rhs: Mat4) -> Self::Output {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, x) in row.iter_mut().enumerate() {
+                *x = (0..4).map(|k| self.0[i][k] * rhs.0[k][j]).sum();
+            }
+        }
+
+        Mat4(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mat4;
+    use crate::lalg::{Point3, Vec3};
+
+    const EPS: f64 = 1e-9;
+
+    fn assert_close(a: Mat4, b: Mat4) {
+        for (row_a, row_b) in a.0.iter().zip(b.0.iter()) {
+            for (x, y) in row_a.iter().zip(row_b) {
+                assert!((x - y).abs() < EPS, "{:?} != {:?}", a, b);
+            }
+        }
+    }
+
+    fn assert_close_vec(a: Vec3, b: Vec3) {
+        assert!((a - b).len() < EPS, "{:?} != {:?}", a, b);
+    }
+
+    fn affine() -> Mat4 {
+        Mat4::translation(Vec3::new(1.0, -2.0, 3.0))
+            * Mat4::rotation_z(30.0)
+            * Mat4::rotation_y(-70.0)
+            * Mat4::rotation_x(15.0)
+            * Mat4::scaling(Vec3::new(2.0, 0.5, -3.0))
+    }
+
+    #[test]
+    fn multiply() {
+        let a = Mat4([
+            [1.0, 2.0, 3.0, 4.0],
+            [5.0, 6.0, 7.0, 8.0],
+            [9.0, 10.0, 11.0, 12.0],
+            [13.0, 14.0, 15.0, 16.0],
+        ]);
+        let b = Mat4([
+            [1.0, 0.0, 2.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0, 1.0],
+        ]);
+
+        assert_eq!(
+            a * b,
+            Mat4([
+                [5.0, 2.0, 5.0, 4.0],
+                [13.0, 6.0, 17.0, 8.0],
+                [21.0, 10.0, 29.0, 12.0],
+                [29.0, 14.0, 41.0, 16.0],
+            ])
+        );
+        assert_eq!(a * Mat4::identity(), a);
+        assert_eq!(Mat4::identity() * a, a);
+    }
+
+    #[test]
+    fn transpose() {
+        let m = affine();
+        let t = m.transpose();
+
+        assert_eq!(t.transpose(), m);
+        for i in 0..4 {
+            for j in 0..4 {
+                assert_eq!(t.0[i][j], m.0[j][i]);
+            }
+        }
+    }
+
+    #[test]
+    fn inverse() {
+        let m = affine();
+        let inv = m.inverse().unwrap();
+
+        assert_close(m * inv, Mat4::identity());
+        assert_close(inv * m, Mat4::identity());
+        assert_close(Mat4::identity().inverse().unwrap(), Mat4::identity());
+    }
+
+    #[test]
+    fn inverse_needs_pivoting() {
+        // a zero on the diagonal, so rows have to be swapped
+        let m = Mat4([
+            [0.0, 1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+
+        assert_close(m.inverse().unwrap(), m);
+    }
+
+    #[test]
+    fn singular_matrix_has_no_inverse() {
+        assert!(Mat4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse().is_none());
+        assert!(Mat4([[1.0; 4]; 4]).inverse().is_none());
+    }
+
+    #[test]
+    fn transform_points_and_vectors() {
+        let m =
+            Mat4::translation(Vec3::new(1.0, 2.0, 3.0)) * Mat4::scaling(Vec3::new(2.0, 3.0, 4.0));
+
+        assert_close_vec(
+            m.transform_point(Point3::new(1.0, 1.0, 1.0)),
+            Point3::new(3.0, 5.0, 7.0),
+        );
+        assert_close_vec(
+            m.transform_vec(Vec3::new(1.0, 1.0, 1.0)),
+            Vec3::new(2.0, 3.0, 4.0),
+        );
+    }
+
+    #[test]
+    fn rotations_are_right_handed() {
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let y = Vec3::new(0.0, 1.0, 0.0);
+        let z = Vec3::new(0.0, 0.0, 1.0);
+
+        assert_close_vec(Mat4::rotation_x(90.0).transform_vec(y), z);
+        assert_close_vec(Mat4::rotation_y(90.0).transform_vec(z), x);
+        assert_close_vec(Mat4::rotation_z(90.0).transform_vec(x), y);
+    }
+}
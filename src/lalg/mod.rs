@@ -1,8 +1,10 @@
 //! Basic implementation of vectors etc
 
+mod mat;
 mod orthnormbasis;
 mod vec;
 
+pub use mat::Mat4;
 pub use orthnormbasis::OrthNormBasis3;
 pub use vec::Vec3;
 
@@ -18,3 +18,58 @@ impl OrthNormBasis3 {
         OrthNormBasis3 { u, v, w }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::OrthNormBasis3;
+    use crate::lalg::{Point3, Vec3};
+
+    const EPS: f64 = 1e-9;
+
+    fn point3() -> impl Strategy<Value = Point3> {
+        (-10.0..10.0, -10.0..10.0, -10.0..10.0).prop_map(|(x, y, z)| Point3::new(x, y, z))
+    }
+
+    #[test]
+    fn looking_down_negative_z() {
+        let onb = OrthNormBasis3::orientation(
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+
+        assert_eq!(onb.u, Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(onb.v, Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(onb.w, Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    proptest! {
+        #[test]
+        fn orientation_is_right_handed_orthonormal(
+            from in point3(),
+            to in point3(),
+            vup in point3(),
+        ) {
+            prop_assume!((from - to).len() > 1e-3);
+            let dir = (from - to).unit_vec();
+            prop_assume!(vup.len() > 1e-3 && vup.unit_vec().cross(dir).len() > 1e-3);
+
+            let onb = OrthNormBasis3::orientation(from, to, vup);
+
+            for e in [onb.u, onb.v, onb.w] {
+                prop_assert!((e.len() - 1.0).abs() < EPS);
+            }
+            prop_assert!(onb.u.dot(onb.v).abs() < EPS);
+            prop_assert!(onb.v.dot(onb.w).abs() < EPS);
+            prop_assert!(onb.w.dot(onb.u).abs() < EPS);
+            prop_assert!((onb.u.cross(onb.v) - onb.w).len() < EPS);
+
+            // `w` points backwards, `v` is as close to `vup` as possible
+            prop_assert!((onb.w - dir).len() < EPS);
+            prop_assert!(onb.v.dot(vup) > 0.0);
+            prop_assert!(onb.u.dot(vup).abs() < 1e-6 * vup.len());
+        }
+    }
+}
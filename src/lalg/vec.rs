@@ -23,18 +23,16 @@ impl Vec3 {
     }
 
     /// Constructs a random vector with values in `[0; 1)`
-    pub fn random() -> Self {
+    pub fn random(rng: &mut (impl Rng + ?Sized)) -> Self {
         Vec3 {
-            x: rand::random::<f64>(),
-            y: rand::random::<f64>(),
-            z: rand::random::<f64>(),
+            x: rng.gen(),
+            y: rng.gen(),
+            z: rng.gen(),
         }
     }
 
     /// Constructs a random vector with values within a given range
-    pub fn random_within_range(range: Range<f64>) -> Self {
-        let mut rng = rand::thread_rng();
-
+    pub fn random_within_range(rng: &mut (impl Rng + ?Sized), range: Range<f64>) -> Self {
         Vec3 {
             x: rng.gen_range(range.clone()),
             y: rng.gen_range(range.clone()),
@@ -43,9 +41,9 @@ impl Vec3 {
     }
 
     /// Constructs a random vector that is in the unit sphere
-    pub fn random_in_unit_sphere() -> Self {
+    pub fn random_in_unit_sphere(rng: &mut (impl Rng + ?Sized)) -> Self {
         loop {
-            let p = Vec3::random_within_range(-1.0..1.0);
+            let p = Vec3::random_within_range(rng, -1.0..1.0);
             if p.len_sq() >= 1.0 {
                 continue;
             }
@@ -55,13 +53,13 @@ impl Vec3 {
     }
 
     /// Constructs a random unit vector
-    pub fn random_unit_vec() -> Self {
-        Vec3::random_in_unit_sphere().unit_vec()
+    pub fn random_unit_vec(rng: &mut (impl Rng + ?Sized)) -> Self {
+        Vec3::random_in_unit_sphere(rng).unit_vec()
     }
 
     /// Constructs a random vector that is in a hemisphere
-    pub fn random_in_hemisphere(normal: Vec3) -> Vec3 {
-        let in_unit_sphere = Vec3::random_in_unit_sphere();
+    pub fn random_in_hemisphere(rng: &mut (impl Rng + ?Sized), normal: Vec3) -> Vec3 {
+        let in_unit_sphere = Vec3::random_in_unit_sphere(rng);
 
         if in_unit_sphere.dot(normal) > 0.0 {
             in_unit_sphere
@@ -71,9 +69,7 @@ impl Vec3 {
     }
 
     /// Constructs a random vector that is in the unit disk
-    pub fn random_in_unit_disk() -> Self {
-        let mut rng = rand::thread_rng();
-
+    pub fn random_in_unit_disk(rng: &mut (impl Rng + ?Sized)) -> Self {
         loop {
             let p = Vec3 {
                 x: rng.gen_range(-1.0..1.0),
@@ -107,6 +103,13 @@ impl Vec3 {
         self - (2.0 * self.dot(n) * n)
     }
 
+    /// Rotate the vector around a unit vector `axis` by `angle` radians (right-hand rule)
+    pub fn rotate_around(self, axis: Vec3, angle: f64) -> Vec3 {
+        let (sin, cos) = angle.sin_cos();
+
+        (self * cos) + (axis.cross(self) * sin) + (axis * axis.dot(self) * (1.0 - cos))
+    }
+
     /// Refract the vector using a unit vector `n` and the quotient of the refractive indices
     pub fn refract(self, n: Vec3, etai_over_etat: f64) -> Vec3 {
         let cos_theta = (-self).dot(n).min(1.0);
@@ -299,3 +302,100 @@ impl From<Vec3> for (f64, f64, f64) {
         (v.x, v.y, v.z)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use proptest::prelude::*;
+
+    use super::Vec3;
+
+    const EPS: f64 = 1e-9;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).len() < EPS, "{:?} != {:?}", a, b);
+    }
+
+    fn vec3() -> impl Strategy<Value = Vec3> {
+        (-10.0..10.0, -10.0..10.0, -10.0..10.0).prop_map(|(x, y, z)| Vec3::new(x, y, z))
+    }
+
+    fn unit_vec3() -> impl Strategy<Value = Vec3> {
+        vec3()
+            .prop_filter("too short to normalize", |v| v.len() > 1e-3)
+            .prop_map(Vec3::unit_vec)
+    }
+
+    #[test]
+    fn reflect_off_floor() {
+        let v = Vec3::new(1.0, -1.0, 0.0);
+        let n = Vec3::new(0.0, 1.0, 0.0);
+
+        assert_close(v.reflect(n), Vec3::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn refract_without_change_of_medium() {
+        let v = Vec3::new(1.0, -2.0, 0.5).unit_vec();
+        let n = Vec3::new(0.0, 1.0, 0.0);
+
+        assert_close(v.refract(n, 1.0), v);
+    }
+
+    #[test]
+    fn refract_head_on() {
+        let v = Vec3::new(0.0, -1.0, 0.0);
+        let n = Vec3::new(0.0, 1.0, 0.0);
+
+        assert_close(v.refract(n, 1.5), v);
+    }
+
+    #[test]
+    fn rotate_around_z() {
+        let v = Vec3::new(1.0, 0.0, 0.0);
+        let axis = Vec3::new(0.0, 0.0, 1.0);
+
+        assert_close(v.rotate_around(axis, FRAC_PI_2), Vec3::new(0.0, 1.0, 0.0));
+    }
+
+    proptest! {
+        #[test]
+        fn reflect_flips_normal_component(v in vec3(), n in unit_vec3()) {
+            let r = v.reflect(n);
+
+            prop_assert!((r.len() - v.len()).abs() < EPS);
+            prop_assert!((r.dot(n) + v.dot(n)).abs() < EPS);
+            prop_assert!((r - r.dot(n) * n - (v - v.dot(n) * n)).len() < EPS);
+        }
+
+        #[test]
+        fn refract_obeys_snell(v in unit_vec3(), n in unit_vec3(), eta in 0.5..2.0) {
+            let cos_i = -v.dot(n);
+            prop_assume!(cos_i > 0.01);
+
+            // no total internal reflection
+            let sin_i = (1.0 - cos_i * cos_i).sqrt();
+            prop_assume!(eta * sin_i < 0.99);
+
+            let r = v.refract(n, eta);
+            let sin_t = r.cross(n).len();
+
+            prop_assert!((r.len() - 1.0).abs() < EPS);
+            prop_assert!(r.dot(n) < 0.0);
+            prop_assert!((sin_t - eta * sin_i).abs() < 1e-6);
+        }
+
+        #[test]
+        fn rotate_around_keeps_length_and_axis(
+            v in vec3(),
+            axis in unit_vec3(),
+            angle in -10.0..10.0,
+        ) {
+            let r = v.rotate_around(axis, angle);
+
+            prop_assert!((r.len() - v.len()).abs() < 1e-6);
+            prop_assert!((r.dot(axis) - v.dot(axis)).abs() < 1e-6);
+        }
+    }
+}
@@ -1,17 +1,23 @@
 use std::{
+    io::{self, IsTerminal},
+    process,
     sync::{atomic, Arc},
     time::Duration,
 };
 
+use clap::Parser;
 use terminal::{Action, Clear, Event, KeyCode, KeyModifiers, Retrieved, Value};
 
 use raytrascii::{
     camera::{
-        direction::{MoveDirection, RotationDirection},
+        direction::{MoveDirection, RotationDirection, ZoomDirection},
         Camera,
     },
     color::Color,
-    render::{RenderDimensions, RenderMode},
+    render::{
+        image::render_to_file, render_to_ansi, render_to_text, text_dimensions, Accumulator,
+        ColorDepth, OutputSettings, RenderMode, RenderSettings, Screen,
+    },
     scene::{
         hittable::{AaRect, AaRectPlaneCoords, Sphere},
         material::{Lambertian, Metal},
@@ -19,7 +25,63 @@ use raytrascii::{
     },
 };
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+use cli::Args;
+
+mod cli;
+
+fn main() {
+    let args = Args::parse();
+
+    if let Err(err) = run(args) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    // load scene
+    let scene = match &args.scene {
+        Some(path) => {
+            Scene::from_file(path).map_err(|err| format!("{}: {}", path.display(), err))?
+        }
+        None => default_scene(),
+    };
+
+    // create camera
+    let mut cam = initial_camera(&args, scene.camera.clone().unwrap_or_default())?;
+
+    let settings = RenderSettings {
+        seed: args.seed,
+        ..RenderSettings::new(args.depth as usize, args.samples as usize)
+    };
+    let output = OutputSettings {
+        color_depth: ColorDepth::from(args.colors),
+        dither: args.dither,
+        dimensions: args.dimensions(),
+        ..OutputSettings::new(RenderMode::from(args.mode), args.palette())
+    };
+
+    if let Some(path) = &args.output {
+        let (width, height) = args.resolution;
+        render_to_file(&scene, &cam, width, height, &settings, path)
+            .map_err(|err| format!("{}: {}", path.display(), err))?;
+        return Ok(());
+    }
+
+    if args.once {
+        // colors only for a terminal, plain lines if the output is piped
+        let (cols, rows) = text_dimensions(output.dimensions)?;
+        let text = if io::stdout().is_terminal() {
+            render_to_ansi(&scene, &cam, cols, rows, &settings, &output)
+        } else {
+            render_to_text(&scene, &cam, cols, rows, &settings, &output)
+        };
+        println!("{}", text);
+        return Ok(());
+    }
+
+    let mut term = terminal::stdout();
+
     // ctrl-c handling
     let running = Arc::new(atomic::AtomicBool::new(true));
     let r = Arc::clone(&running);
@@ -29,6 +91,140 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     })
     .expect("Error setting Ctrl-c handler");
 
+    // set up terminal
+    term.act(Action::HideCursor)?;
+    term.act(Action::EnableRawMode)?;
+    term.act(Action::EnableMouseCapture)?;
+
+    let mut screen = Screen::new();
+    let mut acc = Accumulator::with_max_samples(args.max_samples as usize);
+
+    while running.load(atomic::Ordering::SeqCst) {
+        raytrascii::render::render_progressive(
+            &mut term,
+            &mut screen,
+            &mut acc,
+            &scene,
+            &cam,
+            &settings,
+            &output,
+        )?;
+
+        const MOVEMENT_SPEED: f64 = 0.03;
+        const ROTATION_SPEED: f64 = 2.0;
+        const ZOOM_SPEED: f64 = 2.0;
+
+        if let Retrieved::Event(Some(Event::Key(key_event))) =
+            term.get(Value::Event(Some(Duration::from_millis(50))))?
+        {
+            match key_event.code {
+                KeyCode::Esc => break,
+                KeyCode::Up => {
+                    if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+                        cam.move_relative(MoveDirection::Up, MOVEMENT_SPEED);
+                    } else {
+                        cam.move_relative(MoveDirection::Forward, MOVEMENT_SPEED);
+                    }
+                }
+                KeyCode::Down => {
+                    if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+                        cam.move_relative(MoveDirection::Down, MOVEMENT_SPEED);
+                    } else {
+                        cam.move_relative(MoveDirection::Backward, MOVEMENT_SPEED);
+                    }
+                }
+                KeyCode::Left => {
+                    if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+                        cam.rotate(RotationDirection::Left, ROTATION_SPEED);
+                    } else {
+                        cam.move_relative(MoveDirection::Left, MOVEMENT_SPEED);
+                    }
+                }
+                KeyCode::Right => {
+                    if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+                        cam.rotate(RotationDirection::Right, ROTATION_SPEED);
+                    } else {
+                        cam.move_relative(MoveDirection::Right, MOVEMENT_SPEED);
+                    }
+                }
+                KeyCode::Char('w') => cam.rotate(RotationDirection::Up, ROTATION_SPEED),
+                KeyCode::Char('s') => cam.rotate(RotationDirection::Down, ROTATION_SPEED),
+                KeyCode::Char('a') => cam.rotate(RotationDirection::Left, ROTATION_SPEED),
+                KeyCode::Char('d') => cam.rotate(RotationDirection::Right, ROTATION_SPEED),
+                KeyCode::Char('q') => cam.rotate(RotationDirection::RollLeft, ROTATION_SPEED),
+                KeyCode::Char('e') => cam.rotate(RotationDirection::RollRight, ROTATION_SPEED),
+                KeyCode::Char('+') => cam.zoom(ZoomDirection::In, ZOOM_SPEED),
+                KeyCode::Char('-') => cam.zoom(ZoomDirection::Out, ZOOM_SPEED),
+                KeyCode::Char('f') => {
+                    cam.autofocus(&scene);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // reset terminal
+    term.act(Action::ClearTerminal(Clear::All))?;
+    term.act(Action::DisableMouseCapture)?;
+    term.act(Action::DisableRawMode)?;
+    term.act(Action::ShowCursor)?;
+
+    Ok(())
+}
+
+/// Apply the camera pose given on the command line to the camera of the scene
+fn initial_camera(args: &Args, cam: Camera) -> Result<Camera, String> {
+    let pos = args.pos.map_or(cam.pos(), Into::into);
+    let lookat = args.lookat.map_or(cam.lookat(), Into::into);
+    let vup = args.vup.map_or(cam.vup(), Into::into);
+    let vfov = args.vfov.unwrap_or(cam.vfov());
+
+    if (lookat - pos).near_zero() {
+        return Err(String::from("camera position and lookat point must differ"));
+    }
+
+    if vup.near_zero() {
+        return Err(String::from("camera view up vector must not be zero"));
+    }
+
+    // written so that NaN is rejected as well
+    if !(vfov > 0.0 && vfov < 180.0) {
+        return Err(format!(
+            "invalid vertical field of view: {} (must be in (0; 180))",
+            vfov
+        ));
+    }
+
+    let mut new_cam = Camera::new(pos, lookat, vup, vfov);
+    new_cam.set_shutter(cam.shutter_open(), cam.shutter_close());
+
+    match args.aperture.unwrap_or(cam.aperture()) {
+        aperture if aperture >= 0.0 && aperture.is_finite() => new_cam.set_aperture(aperture),
+        aperture => {
+            return Err(format!(
+                "invalid aperture: {} (must be finite and not negative)",
+                aperture
+            ))
+        }
+    }
+
+    match args.focus_dist.unwrap_or(cam.focus_dist()) {
+        focus_dist if focus_dist > 0.0 && focus_dist.is_finite() => {
+            new_cam.set_focus_dist(focus_dist)
+        }
+        focus_dist => {
+            return Err(format!(
+                "invalid focus distance: {} (must be finite and positive)",
+                focus_dist
+            ))
+        }
+    }
+
+    Ok(new_cam)
+}
+
+/// Scene that is rendered when no scene file is given
+fn default_scene() -> Scene {
     let test_rect = AaRect::new(
         // (3.0, 5.0),
         // (1.0, 3.0),
@@ -40,8 +236,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Lambertian::new(Color::from_u8(255, 255, 0)).boxed(),
     );
 
-    // create scene
-    let scene = Scene::builder(SceneBackground::Solid(Color::white()))
+    Scene::builder(SceneBackground::Solid(Color::white()))
         // ground
         .add_object(
             Sphere::builder()
@@ -67,76 +262,6 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .build_boxed(),
         )
         .add_object(Box::new(test_rect))
-        .build();
-
-    // create camera
-    let mut cam = Camera::default();
-
-    // set up terminal
-    let mut term = terminal::stdout();
-    term.act(Action::HideCursor)?;
-    term.act(Action::EnableRawMode)?;
-    term.act(Action::EnableMouseCapture)?;
-
-    while running.load(atomic::Ordering::SeqCst) {
-        raytrascii::render::render(
-            &mut term,
-            &scene,
-            &cam,
-            RenderDimensions::TermSize,
-            15,
-            10,
-            RenderMode::ColorAndBrightness,
-        )?;
-
-        const MOVEMENT_SPEED: f64 = 0.03;
-
-        if let Retrieved::Event(Some(event)) =
-            term.get(Value::Event(Some(Duration::from_millis(50))))?
-        {
-            match event {
-                Event::Key(key_event) => match key_event.code {
-                    KeyCode::Esc => break,
-                    KeyCode::Up => {
-                        if key_event.modifiers.contains(KeyModifiers::CONTROL) {
-                            cam.move_relative(MoveDirection::Up, MOVEMENT_SPEED);
-                        } else {
-                            cam.move_relative(MoveDirection::Forward, MOVEMENT_SPEED);
-                        }
-                    }
-                    KeyCode::Down => {
-                        if key_event.modifiers.contains(KeyModifiers::CONTROL) {
-                            cam.move_relative(MoveDirection::Down, MOVEMENT_SPEED);
-                        } else {
-                            cam.move_relative(MoveDirection::Backward, MOVEMENT_SPEED);
-                        }
-                    }
-                    KeyCode::Left => {
-                        if key_event.modifiers.contains(KeyModifiers::CONTROL) {
-                            cam.rotate(RotationDirection::Left, MOVEMENT_SPEED);
-                        } else {
-                            cam.move_relative(MoveDirection::Left, MOVEMENT_SPEED);
-                        }
-                    }
-                    KeyCode::Right => {
-                        if key_event.modifiers.contains(KeyModifiers::CONTROL) {
-                            cam.rotate(RotationDirection::Right, MOVEMENT_SPEED);
-                        } else {
-                            cam.move_relative(MoveDirection::Right, MOVEMENT_SPEED);
-                        }
-                    }
-                    _ => (),
-                },
-                _ => (),
-            }
-        }
-    }
-
-    // reset terminal
-    term.act(Action::ClearTerminal(Clear::All))?;
-    term.act(Action::DisableMouseCapture)?;
-    term.act(Action::DisableRawMode)?;
-    term.act(Action::ShowCursor)?;
-
-    Ok(())
+        .use_bvh(true)
+        .build()
 }
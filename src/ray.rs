@@ -3,16 +3,23 @@
 use crate::lalg::{Point3, Vec3};
 
 /// Ray structure that stores an origin a direction
+/// and the point in time at which the ray exists
 #[derive(Debug, Clone, Default)]
 pub struct Ray {
     pub origin: Point3,
     pub dir: Vec3,
+    pub time: f64,
 }
 
 impl Ray {
-    /// Default shorthand constructor
+    /// Default shorthand constructor (for a ray at time `0`)
     pub fn new(origin: Point3, dir: Vec3) -> Self {
-        Ray { origin, dir }
+        Ray::with_time(origin, dir, 0.0)
+    }
+
+    /// Constructs a ray that exists at a specific point in time
+    pub fn with_time(origin: Point3, dir: Vec3, time: f64) -> Self {
+        Ray { origin, dir, time }
     }
 
     /// Return the [point][Point3] located at distance `d` in the ray direction from the origin
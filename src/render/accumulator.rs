@@ -0,0 +1,123 @@
+//! Progressive accumulation of samples over several frames
+
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::scene::Scene;
+
+use super::{render_to_buffer, FrameBuffer, RenderSettings};
+
+/// Per-pixel accumulation buffer for progressive rendering
+///
+/// Every call to [accumulate][Accumulator::accumulate] adds the samples of a new frame,
+/// so a still image converges to a clean result over time.
+/// The accumulated samples are dropped automatically when the camera, the size
+/// or the settings change. Call [reset][Accumulator::reset] when the scene changes.
+#[derive(Debug, Clone)]
+pub struct Accumulator {
+    sum: Vec<Color>,
+    samples: usize,
+    max_samples: Option<usize>,
+    key: Option<AccumulationKey>,
+}
+
+/// Everything that invalidates the accumulated samples when changed
+#[derive(Debug, Clone, PartialEq)]
+struct AccumulationKey {
+    cam: Camera,
+    width: usize,
+    height: usize,
+    max_depth: usize,
+    pixel_aspect_ratio: f64,
+}
+
+impl Accumulator {
+    /// Constructs an empty accumulator that never stops adding samples
+    pub fn new() -> Self {
+        Accumulator {
+            sum: Vec::new(),
+            samples: 0,
+            max_samples: None,
+            key: None,
+        }
+    }
+
+    /// Constructs an empty accumulator that stops ray tracing after `max_samples` samples per pixel
+    pub fn with_max_samples(max_samples: usize) -> Self {
+        Accumulator {
+            max_samples: Some(max_samples),
+            ..Accumulator::new()
+        }
+    }
+
+    /// Drop all accumulated samples
+    pub fn reset(&mut self) {
+        self.sum.clear();
+        self.samples = 0;
+        self.key = None;
+    }
+
+    /// Return the number of accumulated samples per pixel
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Return `true` if the maximum number of samples is reached
+    pub fn is_converged(&self) -> bool {
+        self.max_samples
+            .is_some_and(|max_samples| self.samples >= max_samples)
+    }
+
+    /// Add a new frame to the accumulation and return the average of all frames
+    ///
+    /// If the accumulation is [converged][Accumulator::is_converged], no rays are traced.
+    pub fn accumulate(
+        &mut self,
+        scene: &Scene,
+        cam: &Camera,
+        width: usize,
+        height: usize,
+        settings: &RenderSettings,
+    ) -> FrameBuffer {
+        let key = AccumulationKey {
+            cam: cam.clone(),
+            width,
+            height,
+            max_depth: settings.max_depth,
+            pixel_aspect_ratio: settings.pixel_aspect_ratio,
+        };
+
+        if self.key.as_ref() != Some(&key) {
+            self.reset();
+            self.sum = vec![Color::black(); width * height];
+            self.key = Some(key);
+        }
+
+        if !self.is_converged() {
+            // every frame needs new random numbers to converge
+            let settings = RenderSettings {
+                seed: settings.seed.wrapping_add(self.samples as u64),
+                ..settings.clone()
+            };
+
+            let frame = render_to_buffer(scene, cam, width, height, &settings);
+            let weight = settings.samples_per_pixel as f64;
+
+            for (sum, &color) in self.sum.iter_mut().zip(frame.pixels()) {
+                *sum += color * weight;
+            }
+
+            self.samples += settings.samples_per_pixel;
+        }
+
+        let scale = 1.0 / self.samples.max(1) as f64;
+        let pixels = self.sum.iter().map(|&sum| sum * scale).collect();
+
+        FrameBuffer::from_pixels(width, height, pixels)
+    }
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,159 @@
+//! Text with ANSI escape sequences for colors
+
+use std::fmt::Write;
+
+use super::cell::Cell;
+
+/// Escape sequence that resets all colors
+const RESET: &str = "\x1b[0m";
+
+/// Write rows of cells as lines of text, setting the colors with SGR escape sequences
+///
+/// Every line ends with the default colors, so the text can be printed anywhere.
+pub(crate) fn cells_to_ansi(rows: &[Vec<Cell>]) -> String {
+    let mut text = String::new();
+
+    for (i, cells) in rows.iter().enumerate() {
+        if i > 0 {
+            text.push('\n');
+        }
+
+        let mut current = (None, None);
+
+        for cell in cells {
+            let next = (cell.fg, cell.bg);
+
+            if next != current {
+                // a color can only be unset by resetting both
+                if (current.0.is_some() && next.0.is_none())
+                    || (current.1.is_some() && next.1.is_none())
+                {
+                    text.push_str(RESET);
+                    current = (None, None);
+                }
+
+                if let Some(fg) = next.0.filter(|&fg| current.0 != Some(fg)) {
+                    set_color(&mut text, 38, fg);
+                }
+
+                if let Some(bg) = next.1.filter(|&bg| current.1 != Some(bg)) {
+                    set_color(&mut text, 48, bg);
+                }
+
+                current = next;
+            }
+
+            text.push(cell.ch);
+        }
+
+        if current != (None, None) {
+            text.push_str(RESET);
+        }
+    }
+
+    text
+}
+
+/// Append the escape sequence that sets the foreground (`38`) or background (`48`) color
+fn set_color(text: &mut String, layer: u8, color: terminal::Color) {
+    let _ = match color {
+        terminal::Color::Rgb(r, g, b) => write!(text, "\x1b[{};2;{};{};{}m", layer, r, g, b),
+        terminal::Color::AnsiValue(value) => write!(text, "\x1b[{};5;{}m", layer, value),
+        // `39` and `49` select the default colors
+        terminal::Color::Reset => write!(text, "\x1b[{}m", layer + 1),
+        named => write!(text, "\x1b[{};5;{}m", layer, ansi_index(named)),
+    };
+}
+
+/// Return the index of one of the 16 ANSI colors in the xterm-256 palette
+fn ansi_index(color: terminal::Color) -> u8 {
+    match color {
+        terminal::Color::Black => 0,
+        terminal::Color::DarkRed => 1,
+        terminal::Color::DarkGreen => 2,
+        terminal::Color::DarkYellow => 3,
+        terminal::Color::DarkBlue => 4,
+        terminal::Color::DarkMagenta => 5,
+        terminal::Color::DarkCyan => 6,
+        terminal::Color::Grey => 7,
+        terminal::Color::DarkGrey => 8,
+        terminal::Color::Red => 9,
+        terminal::Color::Green => 10,
+        terminal::Color::Yellow => 11,
+        terminal::Color::Blue => 12,
+        terminal::Color::Magenta => 13,
+        terminal::Color::Cyan => 14,
+        _ => 15,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use terminal::Color as Term;
+
+    use super::*;
+
+    fn cell(ch: char, fg: Option<Term>, bg: Option<Term>) -> Cell {
+        Cell { ch, fg, bg }
+    }
+
+    #[test]
+    fn plain_cells() {
+        let rows = vec![
+            vec![cell('a', None, None), cell('b', None, None)],
+            vec![cell('c', None, None), cell('d', None, None)],
+        ];
+
+        assert_eq!(cells_to_ansi(&rows), "ab\ncd");
+    }
+
+    #[test]
+    fn color_encodings() {
+        let cases = [
+            (Term::Rgb(1, 2, 3), "\x1b[38;2;1;2;3m"),
+            (Term::AnsiValue(196), "\x1b[38;5;196m"),
+            (Term::Black, "\x1b[38;5;0m"),
+            (Term::Grey, "\x1b[38;5;7m"),
+            (Term::DarkGrey, "\x1b[38;5;8m"),
+            (Term::White, "\x1b[38;5;15m"),
+            (Term::Reset, "\x1b[39m"),
+        ];
+
+        for (color, expected) in cases {
+            let rows = vec![vec![cell('x', Some(color), None)]];
+            assert_eq!(
+                cells_to_ansi(&rows),
+                format!("{}x{}", expected, RESET),
+                "{:?}",
+                color
+            );
+        }
+    }
+
+    #[test]
+    fn runs_share_colors() {
+        let red = Some(Term::AnsiValue(196));
+        let rows = vec![vec![
+            cell('a', red, None),
+            cell('b', red, None),
+            cell('c', red, Some(Term::AnsiValue(21))),
+            cell('d', None, None),
+        ]];
+
+        assert_eq!(
+            cells_to_ansi(&rows),
+            "\x1b[38;5;196mab\x1b[48;5;21mc\x1b[0md"
+        );
+    }
+
+    #[test]
+    fn lines_end_with_default_colors() {
+        let red = Some(Term::AnsiValue(196));
+        let rows = vec![vec![cell('a', red, None)], vec![cell('b', red, None)]];
+
+        assert_eq!(
+            cells_to_ansi(&rows),
+            "\x1b[38;5;196ma\x1b[0m\n\x1b[38;5;196mb\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,527 @@
+//! Conversion of rendered pixels to terminal cells
+
+use crate::color::Color;
+
+use super::geometry::{GeometryBuffer, SurfaceSample};
+use super::{Background, FrameBuffer, OutputSettings, RenderMode};
+
+/// Gamma that is applied to colors before they are written to the terminal
+const TERM_GAMMA: f64 = 2.0;
+
+/// Upper half block whose foreground is the upper and background the lower pixel
+const UPPER_HALF_BLOCK: char = '▀';
+
+/// Braille pattern without any raised dots
+const BRAILLE_BLANK: u32 = 0x2800;
+
+/// Bit of each dot of a Braille pattern, indexed by `[row][col]`
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// Brightness from which on a dot of a Braille pattern is raised
+const BRAILLE_THRESHOLD: f64 = 0.5;
+
+/// Relative deviation from a linear change in depth from which on neighboring pixels are separated by an edge
+const EDGE_DEPTH_THRESHOLD: f64 = 0.1;
+
+/// Cosine of the angle between normals from which on neighboring pixels are separated by a crease
+const EDGE_NORMAL_THRESHOLD: f64 = 0.8;
+
+/// A single character cell of the terminal output
+///
+/// Cells are composed with gamma corrected [colors][Color]
+/// and then quantized to the colors the terminal supports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Cell<C = terminal::Color> {
+    pub(crate) ch: char,
+    pub(crate) fg: Option<C>,
+    pub(crate) bg: Option<C>,
+}
+
+impl<C> Cell<C> {
+    fn plain(ch: char) -> Self {
+        Cell {
+            ch,
+            fg: None,
+            bg: None,
+        }
+    }
+}
+
+impl Cell<Color> {
+    /// Convert the colors to the color depth of the output
+    fn quantize(self, output: &OutputSettings, col: usize, row: usize) -> Cell {
+        let quantize = |color: Color| {
+            if output.dither {
+                output.color_depth.quantize_dithered(color, col, row)
+            } else {
+                output.color_depth.quantize(color)
+            }
+        };
+
+        Cell {
+            ch: self.ch,
+            fg: self.fg.map(quantize),
+            bg: self.bg.map(quantize),
+        }
+    }
+}
+
+/// Convert a [FrameBuffer] to rows of cells
+///
+/// The buffer must have the number of pixels per cell of the mode in each direction.
+/// [RenderMode::Edges] also needs the geometry of the same pixels.
+pub(crate) fn to_cells(
+    buffer: &FrameBuffer,
+    geometry: Option<&GeometryBuffer>,
+    output: &OutputSettings,
+) -> Vec<Vec<Cell>> {
+    let (cell_width, cell_height) = output.mode.pixels_per_cell();
+    let cols = buffer.width() / cell_width;
+    let rows = buffer.height() / cell_height;
+
+    (0..rows)
+        .map(|row| {
+            (0..cols)
+                .map(|col| {
+                    // gamma corrected pixels of the cell, row by row
+                    let pixel = |x: usize, y: usize| {
+                        buffer
+                            .get(col * cell_width + x, row * cell_height + y)
+                            .correct(TERM_GAMMA, 1)
+                    };
+
+                    let cell = match output.mode {
+                        RenderMode::Brightness => {
+                            Cell::plain(output.palette.char_for(pixel(0, 0).brightness()))
+                        }
+                        RenderMode::Color => Cell {
+                            fg: Some(pixel(0, 0)),
+                            ..Cell::plain('#')
+                        },
+                        RenderMode::ColorAndBrightness => {
+                            let color = pixel(0, 0);
+                            Cell {
+                                fg: Some(color),
+                                ..Cell::plain(output.palette.char_for(color.brightness()))
+                            }
+                        }
+                        RenderMode::HalfBlock => Cell {
+                            ch: UPPER_HALF_BLOCK,
+                            fg: Some(pixel(0, 0)),
+                            bg: Some(pixel(0, 1)),
+                        },
+                        RenderMode::Braille => braille_cell(pixel, output.palette.background()),
+                        RenderMode::Edges => {
+                            let geometry = geometry.expect("Edges mode requires geometry");
+                            let color = pixel(0, 0);
+                            let ch = edge_glyph(geometry, col as isize, row as isize)
+                                .unwrap_or_else(|| output.palette.char_for(color.brightness()));
+
+                            Cell {
+                                fg: Some(color),
+                                ..Cell::plain(ch)
+                            }
+                        }
+                    };
+
+                    cell.quantize(output, col, row)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Compose a Braille pattern of the 2x4 pixels of a cell
+///
+/// Dots are raised where the palette would use dense characters,
+/// the foreground is the average color of the raised dots.
+fn braille_cell(pixel: impl Fn(usize, usize) -> Color, background: Background) -> Cell<Color> {
+    let mut pattern = BRAILLE_BLANK;
+    let mut color = Color::black();
+    let mut raised = 0;
+
+    for (y, bits) in BRAILLE_DOTS.iter().enumerate() {
+        for (x, &bit) in bits.iter().enumerate() {
+            let px = pixel(x, y);
+            let bright = px.brightness() >= BRAILLE_THRESHOLD;
+            if bright == (background == Background::Dark) {
+                pattern |= bit;
+                color += px;
+                raised += 1;
+            }
+        }
+    }
+
+    let ch = char::from_u32(pattern).expect("Braille patterns are valid characters");
+
+    if raised == 0 {
+        return Cell::plain(ch);
+    }
+
+    Cell {
+        fg: Some(color / raised as f64),
+        ..Cell::plain(ch)
+    }
+}
+
+/// Choose a directional glyph if the pixel lies on a silhouette or crease
+///
+/// Only the pixel in front draws a silhouette, so outlines are one character wide.
+/// The glyph runs perpendicular to the direction of the separated neighbors.
+fn edge_glyph(geometry: &GeometryBuffer, x: isize, y: isize) -> Option<char> {
+    let sample = geometry.get(x, y)?;
+
+    let (mut dx, mut dy) = (0, 0);
+    for ny in -1..=1 {
+        for nx in -1..=1 {
+            // the border of the image is no edge
+            if (nx, ny) == (0, 0) || !geometry.contains(x + nx, y + ny) {
+                continue;
+            }
+
+            let neighbor = geometry.get(x + nx, y + ny);
+
+            // the depth is extrapolated from the opposite neighbor,
+            // which is unknown beyond the border of the image
+            let expected_depth = geometry
+                .contains(x - nx, y - ny)
+                .then(|| expected_depth(sample, geometry.get(x - nx, y - ny)));
+
+            if is_separated(sample, neighbor, expected_depth) {
+                dx += nx;
+                dy += ny;
+            }
+        }
+    }
+
+    if (dx, dy) == (0, 0) {
+        return None;
+    }
+
+    // angle of the separated side with the y axis pointing up
+    let angle = (-dy as f64).atan2(dx as f64).to_degrees();
+    let sector = ((angle + 180.0 + 22.5) / 45.0) as usize % 8;
+
+    let glyph = match sector {
+        0 | 4 => '|',
+        1 | 5 => '\\',
+        2 => '_',
+        3 | 7 => '/',
+        _ => '-',
+    };
+
+    Some(glyph)
+}
+
+/// Return the depth of the neighbor on the other side of a sample than `opposite` if the surface is flat
+///
+/// The inverse of the depth changes linearly across a flat surface in the image,
+/// it is infinite where the surface reaches the horizon.
+fn expected_depth(sample: SurfaceSample, opposite: Option<SurfaceSample>) -> f64 {
+    let Some(opposite) = opposite else {
+        return sample.depth;
+    };
+
+    let inverse = 2.0 / sample.depth - 1.0 / opposite.depth;
+
+    if inverse > 0.0 {
+        1.0 / inverse
+    } else {
+        f64::INFINITY
+    }
+}
+
+/// Return whether an edge separates a sample from a neighbor behind or next to it
+///
+/// The neighbor is compared with the [expected depth][expected_depth] of a flat surface,
+/// so flat surfaces seen at a grazing angle do not count as edges.
+/// Without an expected depth only creases are detected.
+fn is_separated(
+    sample: SurfaceSample,
+    neighbor: Option<SurfaceSample>,
+    expected_depth: Option<f64>,
+) -> bool {
+    let Some(neighbor) = neighbor else {
+        return true;
+    };
+
+    if let Some(expected_depth) = expected_depth {
+        if neighbor.depth - expected_depth.max(sample.depth) > EDGE_DEPTH_THRESHOLD * sample.depth {
+            return true;
+        }
+    }
+
+    // creases are drawn by the nearer side of surfaces at a similar depth
+    neighbor.depth >= sample.depth && sample.normal.dot(neighbor.normal) < EDGE_NORMAL_THRESHOLD
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lalg::Vec3;
+    use crate::render::Palette;
+
+    fn sample(depth: f64) -> SurfaceSample {
+        SurfaceSample {
+            normal: Vec3::new(0.0, 0.0, 1.0),
+            depth,
+        }
+    }
+
+    fn tilted(depth: f64, normal: Vec3) -> SurfaceSample {
+        SurfaceSample {
+            normal: normal.unit_vec(),
+            depth,
+        }
+    }
+
+    /// 3x3 samples of a flat wall facing the camera, without the neighbor at (`1 + nx`, `1 + ny`)
+    fn wall_without(nx: isize, ny: isize) -> GeometryBuffer {
+        let samples = (0..9)
+            .map(|i| (i % 3 - 1, i / 3 - 1))
+            .map(|pos| (pos != (nx, ny)).then(|| sample(1.0)))
+            .collect();
+
+        GeometryBuffer::new(3, 3, samples)
+    }
+
+    /// Pixels of a Braille cell that are bright only at (`bx`, `by`)
+    fn single_dot(bx: usize, by: usize) -> impl Fn(usize, usize) -> Color {
+        move |x, y| {
+            if (x, y) == (bx, by) {
+                Color::white()
+            } else {
+                Color::black()
+            }
+        }
+    }
+
+    #[test]
+    fn braille_dot_bits() {
+        let cases = [
+            ((0, 0), '\u{2801}'),
+            ((0, 1), '\u{2802}'),
+            ((0, 2), '\u{2804}'),
+            ((1, 0), '\u{2808}'),
+            ((1, 1), '\u{2810}'),
+            ((1, 2), '\u{2820}'),
+            ((0, 3), '\u{2840}'),
+            ((1, 3), '\u{2880}'),
+        ];
+
+        for ((x, y), expected) in cases {
+            let cell = braille_cell(single_dot(x, y), Background::Dark);
+            assert_eq!(cell.ch, expected, "dot at ({}, {})", x, y);
+            assert_eq!(cell.fg, Some(Color::white()));
+        }
+    }
+
+    #[test]
+    fn braille_all_or_no_dots() {
+        let full = braille_cell(|_, _| Color::white(), Background::Dark);
+        assert_eq!(full.ch, '\u{28FF}');
+
+        let blank = braille_cell(|_, _| Color::black(), Background::Dark);
+        assert_eq!(blank.ch, '\u{2800}');
+        assert_eq!(blank.fg, None);
+    }
+
+    #[test]
+    fn braille_threshold() {
+        let bright = Color::new(0.25, 0.25, 0.25);
+        let dark = Color::new(0.18, 0.18, 0.18);
+        assert!(bright.brightness() >= BRAILLE_THRESHOLD);
+        assert!(dark.brightness() < BRAILLE_THRESHOLD);
+
+        let cases = [
+            (bright, Background::Dark, '\u{28FF}'),
+            (dark, Background::Dark, '\u{2800}'),
+            (bright, Background::Light, '\u{2800}'),
+            (dark, Background::Light, '\u{28FF}'),
+        ];
+
+        for (color, background, expected) in cases {
+            let cell = braille_cell(|_, _| color, background);
+            assert_eq!(cell.ch, expected, "{:?} on {:?}", color, background);
+        }
+    }
+
+    #[test]
+    fn braille_averages_raised_dots() {
+        let pixel = |x: usize, y: usize| match (x, y) {
+            (0, 0) => Color::new(1.0, 1.0, 0.0),
+            (1, 3) => Color::new(0.0, 1.0, 1.0),
+            _ => Color::black(),
+        };
+
+        let cell = braille_cell(pixel, Background::Dark);
+        assert_eq!(cell.ch, '\u{2881}');
+        assert_eq!(cell.fg, Some(Color::new(0.5, 1.0, 0.5)));
+        assert_eq!(cell.bg, None);
+    }
+
+    #[test]
+    fn braille_cells_from_buffer() {
+        let mut pixels = vec![Color::black(); 4 * 4];
+        // right dot of the third row of the second cell
+        pixels[2 * 4 + 3] = Color::white();
+        let buffer = FrameBuffer::from_pixels(4, 4, pixels);
+        let palette = Palette::default().with_background(Background::Dark);
+        let output = OutputSettings::new(RenderMode::Braille, palette);
+
+        let cells = to_cells(&buffer, None, &output);
+        assert_eq!(cells.len(), 1);
+        assert_eq!(cells[0][0].ch, '\u{2800}');
+        assert_eq!(cells[0][1].ch, '\u{2820}');
+    }
+
+    #[test]
+    fn half_block_cells() {
+        // gamma correction takes the square root
+        let pixels = vec![
+            Color::new(1.0, 0.0, 0.25),
+            Color::new(0.0, 0.0, 0.0),
+            Color::new(0.25, 1.0, 0.0),
+            Color::new(1.0, 1.0, 1.0),
+        ];
+        let buffer = FrameBuffer::from_pixels(2, 2, pixels);
+        let output = OutputSettings::new(RenderMode::HalfBlock, Palette::default());
+
+        let cells = to_cells(&buffer, None, &output);
+        let expected = [
+            (
+                terminal::Color::Rgb(255, 0, 127),
+                terminal::Color::Rgb(127, 255, 0),
+            ),
+            (
+                terminal::Color::Rgb(0, 0, 0),
+                terminal::Color::Rgb(255, 255, 255),
+            ),
+        ];
+
+        assert_eq!(cells.len(), 1);
+        for (cell, (fg, bg)) in cells[0].iter().zip(expected) {
+            assert_eq!(cell.ch, UPPER_HALF_BLOCK);
+            assert_eq!(cell.fg, Some(fg));
+            assert_eq!(cell.bg, Some(bg));
+        }
+    }
+
+    #[test]
+    fn glyph_per_direction() {
+        let cases = [
+            ((1, 0), '|'),
+            ((-1, 0), '|'),
+            ((0, -1), '-'),
+            ((0, 1), '_'),
+            ((1, -1), '\\'),
+            ((-1, 1), '\\'),
+            ((1, 1), '/'),
+            ((-1, -1), '/'),
+        ];
+
+        for ((nx, ny), expected) in cases {
+            let geometry = wall_without(nx, ny);
+            assert_eq!(
+                edge_glyph(&geometry, 1, 1),
+                Some(expected),
+                "missing neighbor at ({}, {})",
+                nx,
+                ny
+            );
+        }
+    }
+
+    #[test]
+    fn no_glyph_inside_surface() {
+        let geometry = GeometryBuffer::new(3, 3, vec![Some(sample(1.0)); 9]);
+
+        assert_eq!(edge_glyph(&geometry, 1, 1), None);
+    }
+
+    #[test]
+    fn no_glyph_for_missed_pixel() {
+        let geometry = wall_without(0, 0);
+
+        assert_eq!(edge_glyph(&geometry, 1, 1), None);
+    }
+
+    #[test]
+    fn tilted_plane_reaching_border() {
+        // the inverse depth of a plane changes linearly across the image
+        let (width, height) = (12, 8);
+        let samples = (0..width * height)
+            .map(|i| {
+                let (x, y) = ((i % width) as f64, (i / width) as f64);
+                let depth = 1.0 / (1.0 - 0.05 * x - 0.06 * y);
+                Some(tilted(depth, Vec3::new(1.0, 1.0, 1.0)))
+            })
+            .collect();
+        let geometry = GeometryBuffer::new(width, height, samples);
+
+        for y in 0..height as isize {
+            for x in 0..width as isize {
+                assert_eq!(edge_glyph(&geometry, x, y), None, "({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn depth_threshold() {
+        let near = sample(1.0);
+
+        assert!(!is_separated(near, Some(sample(1.09)), Some(1.0)));
+        assert!(is_separated(near, Some(sample(1.11)), Some(1.0)));
+
+        // only the sample in front draws the silhouette
+        assert!(!is_separated(sample(1.11), Some(near), Some(1.11)));
+
+        // a depth that changes like expected is no edge
+        assert!(!is_separated(near, Some(sample(1.5)), Some(1.45)));
+
+        // a neighbor that misses every object is always separated
+        assert!(is_separated(near, None, Some(1.0)));
+    }
+
+    #[test]
+    fn crease_threshold() {
+        let front = tilted(1.0, Vec3::new(0.0, 0.0, 1.0));
+        let angle = |cos: f64| tilted(1.0, Vec3::new((1.0 - cos * cos).sqrt(), 0.0, cos));
+
+        assert!(!is_separated(front, Some(angle(0.81)), Some(1.0)));
+        assert!(is_separated(front, Some(angle(0.79)), Some(1.0)));
+
+        // the crease is drawn by the nearer side
+        let behind = tilted(1.05, Vec3::new(1.0, 0.0, 0.0));
+        assert!(is_separated(front, Some(behind), Some(1.0)));
+        assert!(!is_separated(behind, Some(front), Some(1.05)));
+    }
+
+    #[test]
+    fn border_only_detects_creases() {
+        let near = sample(1.0);
+
+        // without the opposite neighbor the depth of a flat surface is unknown
+        assert!(!is_separated(near, Some(sample(3.0)), None));
+        assert!(is_separated(
+            near,
+            Some(tilted(1.0, Vec3::new(1.0, 0.0, 0.0))),
+            None
+        ));
+    }
+
+    #[test]
+    fn expected_depth_of_plane() {
+        assert!((expected_depth(sample(2.0), Some(sample(4.0 / 3.0))) - 4.0).abs() < 1e-9);
+        assert_eq!(expected_depth(sample(2.0), Some(sample(2.0))), 2.0);
+
+        // the plane reaches the horizon before the neighbor
+        assert_eq!(
+            expected_depth(sample(3.0), Some(sample(1.0))),
+            f64::INFINITY
+        );
+
+        // a missed opposite neighbor expects the depth of the sample
+        assert_eq!(expected_depth(sample(2.0), None), 2.0);
+    }
+}
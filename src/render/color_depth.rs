@@ -0,0 +1,286 @@
+//! Colors supported by the terminal
+
+use std::env;
+
+use crate::color::Color;
+
+/// Levels of each channel in the 6x6x6 color cube of xterm-256 (indices 16 to 231)
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The 16 ANSI colors and their usual (xterm) values
+const ANSI_COLORS: [(terminal::Color, [u8; 3]); 16] = [
+    (terminal::Color::Black, [0, 0, 0]),
+    (terminal::Color::DarkRed, [205, 0, 0]),
+    (terminal::Color::DarkGreen, [0, 205, 0]),
+    (terminal::Color::DarkYellow, [205, 205, 0]),
+    (terminal::Color::DarkBlue, [0, 0, 238]),
+    (terminal::Color::DarkMagenta, [205, 0, 205]),
+    (terminal::Color::DarkCyan, [0, 205, 205]),
+    (terminal::Color::Grey, [229, 229, 229]),
+    (terminal::Color::DarkGrey, [127, 127, 127]),
+    (terminal::Color::Red, [255, 0, 0]),
+    (terminal::Color::Green, [0, 255, 0]),
+    (terminal::Color::Yellow, [255, 255, 0]),
+    (terminal::Color::Blue, [92, 92, 255]),
+    (terminal::Color::Magenta, [255, 0, 255]),
+    (terminal::Color::Cyan, [0, 255, 255]),
+    (terminal::Color::White, [255, 255, 255]),
+];
+
+/// 4x4 Bayer matrix for ordered dithering
+const BAYER_MATRIX: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Number of colors a terminal can display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorDepth {
+    /// 24-bit RGB colors
+    #[default]
+    TrueColor,
+    /// The xterm-256 palette
+    Ansi256,
+    /// The 16 ANSI colors
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// Guess the color depth of the terminal from the `COLORTERM` and `TERM` environment variables
+    pub fn detect() -> Self {
+        ColorDepth::from_env_values(
+            env::var("COLORTERM").ok().as_deref(),
+            env::var("TERM").ok().as_deref(),
+        )
+    }
+
+    /// Guess the color depth from the values of `COLORTERM` and `TERM`
+    pub fn from_env_values(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            return ColorDepth::TrueColor;
+        }
+
+        match term {
+            Some(term) if term.contains("truecolor") || term.contains("direct") => {
+                ColorDepth::TrueColor
+            }
+            Some(term) if term.contains("256color") => ColorDepth::Ansi256,
+            _ => ColorDepth::Ansi16,
+        }
+    }
+
+    /// Convert a (gamma corrected) color to the nearest terminal color of this depth
+    pub fn quantize(self, color: Color) -> terminal::Color {
+        match self {
+            ColorDepth::TrueColor => color.into(),
+            ColorDepth::Ansi256 => terminal::Color::AnsiValue(nearest_ansi256(to_rgb(color))),
+            ColorDepth::Ansi16 => nearest_ansi16(to_rgb(color)),
+        }
+    }
+
+    /// Like [quantize][ColorDepth::quantize] but with ordered dithering
+    ///
+    /// The color is offset depending on the position of the cell (`x`, `y`)
+    /// by up to half the distance between the colors of this depth,
+    /// so areas between two colors are drawn as a pattern of both.
+    pub fn quantize_dithered(self, color: Color, x: usize, y: usize) -> terminal::Color {
+        let spread = match self {
+            ColorDepth::TrueColor => return self.quantize(color),
+            ColorDepth::Ansi256 => 1.0 / (CUBE_LEVELS.len() - 1) as f64,
+            ColorDepth::Ansi16 => 0.5,
+        };
+
+        let offset = bayer_threshold(x, y) * spread;
+
+        self.quantize(color + Color::new(offset, offset, offset))
+    }
+}
+
+/// Return the dithering threshold of the cell (`x`, `y`) in (`-0.5`; `0.5`)
+fn bayer_threshold(x: usize, y: usize) -> f64 {
+    (BAYER_MATRIX[y % 4][x % 4] as f64 + 0.5) / 16.0 - 0.5
+}
+
+/// Convert a color to 8-bit values, clamping each channel to [`0`; `1`]
+fn to_rgb(color: Color) -> [u8; 3] {
+    let channel = |x: f64| (x.clamp(0.0, 1.0) * 255.0).round() as u8;
+    [channel(color.r), channel(color.g), channel(color.b)]
+}
+
+/// Return the squared distance of two 8-bit colors
+fn distance_sq(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter()
+        .zip(b)
+        .map(|(&x, y)| (x as i32 - y as i32).pow(2) as u32)
+        .sum()
+}
+
+/// Return the index of the nearest color of the xterm-256 cube or gray ramp
+fn nearest_ansi256(rgb: [u8; 3]) -> u8 {
+    // nearest level of the cube in each channel
+    let level = |x: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - x as i32).abs())
+            .unwrap_or(0)
+    };
+    let [r, g, b] = rgb.map(level);
+    let cube_idx = 16 + (36 * r) + (6 * g) + b;
+    let cube_rgb = [CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]];
+
+    // nearest step of the gray ramp (8 to 238 in steps of 10)
+    let avg = rgb.iter().map(|&x| x as u32).sum::<u32>() / 3;
+    let gray_step = ((avg.saturating_sub(3)) / 10).min(23);
+    let gray_idx = 232 + gray_step as usize;
+    let gray = (8 + 10 * gray_step) as u8;
+
+    if distance_sq(rgb, [gray; 3]) < distance_sq(rgb, cube_rgb) {
+        gray_idx as u8
+    } else {
+        cube_idx as u8
+    }
+}
+
+/// Return the nearest of the 16 ANSI colors
+fn nearest_ansi16(rgb: [u8; 3]) -> terminal::Color {
+    ANSI_COLORS
+        .iter()
+        .min_by_key(|(_, value)| distance_sq(rgb, *value))
+        .map(|&(color, _)| color)
+        .unwrap_or(terminal::Color::White)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use terminal::Color as Term;
+
+    #[test]
+    fn detect_from_env_values() {
+        let cases = [
+            (Some("truecolor"), Some("xterm"), ColorDepth::TrueColor),
+            (Some("24bit"), None, ColorDepth::TrueColor),
+            (None, Some("xterm-direct"), ColorDepth::TrueColor),
+            (None, Some("xterm-truecolor"), ColorDepth::TrueColor),
+            (None, Some("xterm-256color"), ColorDepth::Ansi256),
+            (Some("yes"), Some("screen-256color"), ColorDepth::Ansi256),
+            (None, Some("xterm"), ColorDepth::Ansi16),
+            (None, Some("linux"), ColorDepth::Ansi16),
+            (None, None, ColorDepth::Ansi16),
+        ];
+
+        for (colorterm, term, expected) in cases {
+            assert_eq!(
+                ColorDepth::from_env_values(colorterm, term),
+                expected,
+                "COLORTERM={:?} TERM={:?}",
+                colorterm,
+                term
+            );
+        }
+    }
+
+    #[test]
+    fn ansi256_table() {
+        let cases = [
+            ([0, 0, 0], 16),
+            ([255, 255, 255], 231),
+            ([255, 0, 0], 196),
+            ([0, 255, 0], 46),
+            ([0, 0, 255], 21),
+            ([95, 135, 175], 67),
+            ([215, 175, 0], 178),
+            // nearest cube level in each channel
+            ([250, 100, 40], 202),
+            // gray ramp is nearer than the cube
+            ([8, 8, 8], 232),
+            ([100, 100, 100], 241),
+            ([128, 128, 128], 244),
+            ([238, 238, 238], 255),
+            // cube is nearer than the gray ramp
+            ([95, 95, 95], 59),
+            ([0, 0, 95], 17),
+        ];
+
+        for (rgb, expected) in cases {
+            assert_eq!(nearest_ansi256(rgb), expected, "{:?}", rgb);
+        }
+    }
+
+    #[test]
+    fn ansi16_table() {
+        let cases = [
+            ([0, 0, 0], Term::Black),
+            ([255, 255, 255], Term::White),
+            ([200, 10, 10], Term::DarkRed),
+            ([250, 20, 20], Term::Red),
+            ([0, 190, 0], Term::DarkGreen),
+            ([10, 10, 230], Term::DarkBlue),
+            ([90, 90, 250], Term::Blue),
+            ([128, 128, 128], Term::DarkGrey),
+            ([230, 230, 230], Term::Grey),
+            ([0, 250, 250], Term::Cyan),
+        ];
+
+        for (rgb, expected) in cases {
+            assert_eq!(nearest_ansi16(rgb), expected, "{:?}", rgb);
+        }
+    }
+
+    #[test]
+    fn quantize_clamps() {
+        let color = Color::new(2.0, -1.0, 0.0);
+
+        assert_eq!(ColorDepth::TrueColor.quantize(color), Term::Rgb(255, 0, 0));
+        assert_eq!(ColorDepth::Ansi256.quantize(color), Term::AnsiValue(196));
+        assert_eq!(ColorDepth::Ansi16.quantize(color), Term::Red);
+    }
+
+    #[test]
+    fn bayer_thresholds() {
+        let mut thresholds: Vec<f64> = (0..4)
+            .flat_map(|y| (0..4).map(move |x| bayer_threshold(x, y)))
+            .collect();
+        thresholds.sort_by(f64::total_cmp);
+
+        // one threshold per sixteenth, centered around zero
+        for (i, threshold) in thresholds.iter().enumerate() {
+            let expected = (i as f64 + 0.5) / 16.0 - 0.5;
+            assert!((threshold - expected).abs() < 1e-12);
+        }
+
+        assert_eq!(bayer_threshold(0, 0), -15.0 / 32.0);
+        assert_eq!(bayer_threshold(3, 1), -3.0 / 32.0);
+        assert_eq!(bayer_threshold(5, 6), bayer_threshold(1, 2));
+    }
+
+    #[test]
+    fn dither_mixes_neighbors() {
+        // a quarter gray lies about halfway between black and dark gray
+        let color = Color::new(0.25, 0.25, 0.25);
+        let mut black = 0;
+        let mut gray = 0;
+
+        for y in 0..4 {
+            for x in 0..4 {
+                match ColorDepth::Ansi16.quantize_dithered(color, x, y) {
+                    Term::Black => black += 1,
+                    Term::DarkGrey => gray += 1,
+                    other => panic!("unexpected color {:?}", other),
+                }
+            }
+        }
+
+        assert_eq!(black, 8);
+        assert_eq!(gray, 8);
+    }
+
+    #[test]
+    fn dither_keeps_truecolor() {
+        let color = Color::new(0.2, 0.4, 0.6);
+
+        for (x, y) in [(0, 0), (1, 3), (2, 1)] {
+            assert_eq!(
+                ColorDepth::TrueColor.quantize_dithered(color, x, y),
+                ColorDepth::TrueColor.quantize(color)
+            );
+        }
+    }
+}
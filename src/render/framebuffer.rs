@@ -0,0 +1,78 @@
+//! Buffer of rendered pixels
+
+use crate::color::Color;
+
+/// Two-dimensional buffer of linear (not gamma corrected) [colors][Color]
+///
+/// Pixels are stored row by row, starting with the top left corner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameBuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl FrameBuffer {
+    /// Constructs a black buffer of the given size
+    pub fn new(width: usize, height: usize) -> Self {
+        FrameBuffer {
+            width,
+            height,
+            pixels: vec![Color::black(); width * height],
+        }
+    }
+
+    /// Constructs a buffer from row-major pixels
+    ///
+    /// Panics if the number of pixels does not match the size.
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "Number of pixels does not match the buffer size"
+        );
+
+        FrameBuffer {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Return the width in pixels
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Return the height in pixels
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Return the color of the pixel in column `x` and row `y`
+    pub fn get(&self, x: usize, y: usize) -> Color {
+        self.pixels[self.index(x, y)]
+    }
+
+    /// Set the color of the pixel in column `x` and row `y`
+    pub fn set(&mut self, x: usize, y: usize, color: Color) {
+        let idx = self.index(x, y);
+        self.pixels[idx] = color;
+    }
+
+    /// Return all pixels in row-major order
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    /// Return an iterator over the rows of pixels, starting at the top
+    pub fn rows(&self) -> impl Iterator<Item = &[Color]> {
+        // `max(1)` because `chunks` panics on a chunk size of zero
+        self.pixels.chunks(self.width.max(1))
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        assert!(x < self.width && y < self.height, "Pixel is out of bounds");
+        (y * self.width) + x
+    }
+}
@@ -0,0 +1,96 @@
+//! Geometric information about the surfaces seen in each pixel
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+
+use crate::camera::Camera;
+use crate::lalg::Vec3;
+use crate::scene::hittable::Hittable;
+use crate::scene::Scene;
+
+use super::{pixel_seed, RenderSettings};
+
+/// Surface hit by the primary ray of a pixel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SurfaceSample {
+    /// Unit normal that points against the ray
+    pub(crate) normal: Vec3,
+    /// Distance from the camera
+    pub(crate) depth: f64,
+}
+
+/// Two-dimensional buffer of [surface samples][SurfaceSample] (`None` where no object is hit)
+///
+/// Samples are stored row by row, starting with the top left corner, like in a [FrameBuffer][super::FrameBuffer].
+#[derive(Debug, Clone)]
+pub(crate) struct GeometryBuffer {
+    width: usize,
+    height: usize,
+    samples: Vec<Option<SurfaceSample>>,
+}
+
+impl GeometryBuffer {
+    /// Constructs a buffer of `width` x `height` samples, row by row
+    pub(crate) fn new(width: usize, height: usize, samples: Vec<Option<SurfaceSample>>) -> Self {
+        assert_eq!(samples.len(), width * height, "Wrong number of samples");
+
+        GeometryBuffer {
+            width,
+            height,
+            samples,
+        }
+    }
+
+    /// Return whether column `x` and row `y` lie within the buffer
+    pub(crate) fn contains(&self, x: isize, y: isize) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    /// Return the sample in column `x` and row `y`
+    ///
+    /// Coordinates outside of the buffer count as a miss.
+    pub(crate) fn get(&self, x: isize, y: isize) -> Option<SurfaceSample> {
+        if !self.contains(x, y) {
+            return None;
+        }
+
+        self.samples[(y as usize * self.width) + x as usize]
+    }
+}
+
+/// Trace one ray through the center of each pixel and record the surface it hits
+pub(crate) fn render_geometry(
+    scene: &Scene,
+    cam: &Camera,
+    width: usize,
+    height: usize,
+    settings: &RenderSettings,
+) -> GeometryBuffer {
+    let aspect_ratio = (width as f64 * settings.pixel_aspect_ratio) / height as f64;
+    let view = cam.get_view(aspect_ratio);
+
+    let samples = (0..width * height)
+        .into_par_iter()
+        .map(|idx| {
+            // the buffer starts at the top, the viewport at the bottom
+            let row = height - (idx / width) - 1;
+            let col = idx % width;
+
+            let u = (col as f64 + 0.5) / (width.max(2) - 1) as f64;
+            let v = (row as f64 + 0.5) / (height.max(2) - 1) as f64;
+            let ray = view.get_pinhole_ray(u, v);
+            let mut rng = ChaCha8Rng::seed_from_u64(pixel_seed(settings.seed, col, row));
+
+            scene
+                .objects
+                .hit(&ray, 0.001, f64::INFINITY, &mut rng)
+                .map(|rec| SurfaceSample {
+                    normal: rec.normal,
+                    depth: rec.t * ray.dir.len(),
+                })
+        })
+        .collect();
+
+    GeometryBuffer::new(width, height, samples)
+}
@@ -0,0 +1,367 @@
+//! Export of rendered images to files and import of image files
+
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::scene::Scene;
+
+use super::{render_to_buffer, FrameBuffer, RenderSettings};
+
+/// Gamma that is applied when converting to 8-bit color values
+const GAMMA: f64 = 2.0;
+
+/// File format of an exported image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Plain text portable pixmap (P3)
+    Ppm,
+    /// Portable network graphics (requires the `png` feature)
+    #[cfg(feature = "png")]
+    Png,
+}
+
+impl ImageFormat {
+    /// Guess the format from the extension of a file path
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+
+        match ext.as_str() {
+            "ppm" => Some(ImageFormat::Ppm),
+            #[cfg(feature = "png")]
+            "png" => Some(ImageFormat::Png),
+            _ => None,
+        }
+    }
+}
+
+/// Render a scene seen by a camera and save it as an image file of `width` x `height` pixels
+///
+/// The format is chosen by the file extension of `path`.
+pub fn render_to_file(
+    scene: &Scene,
+    cam: &Camera,
+    width: usize,
+    height: usize,
+    settings: &RenderSettings,
+    path: impl AsRef<Path>,
+) -> io::Result<()> {
+    let buffer = render_to_buffer(scene, cam, width, height, settings);
+    save_image(&buffer, path)
+}
+
+/// Save a [FrameBuffer] as an image file
+///
+/// The format is chosen by the file extension of `path`.
+pub fn save_image(buffer: &FrameBuffer, path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+
+    let format = ImageFormat::from_path(path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unsupported image format: {}", path.display()),
+        )
+    })?;
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_image(buffer, format, &mut writer)?;
+    writer.flush()
+}
+
+/// Write a [FrameBuffer] in a specific image format
+pub fn write_image(
+    buffer: &FrameBuffer,
+    format: ImageFormat,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    match format {
+        ImageFormat::Ppm => write_ppm(buffer, writer),
+        #[cfg(feature = "png")]
+        ImageFormat::Png => write_png(buffer, writer),
+    }
+}
+
+/// Write a [FrameBuffer] as a plain text PPM image
+pub fn write_ppm(buffer: &FrameBuffer, writer: &mut impl Write) -> io::Result<()> {
+    writeln!(writer, "P3")?;
+    writeln!(writer, "{} {}", buffer.width(), buffer.height())?;
+    writeln!(writer, "255")?;
+
+    for row in buffer.rows() {
+        for &color in row {
+            let [r, g, b] = to_rgb8(color);
+            writeln!(writer, "{} {} {}", r, g, b)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a [FrameBuffer] as a PNG image
+#[cfg(feature = "png")]
+pub fn write_png(buffer: &FrameBuffer, writer: &mut impl Write) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(writer, buffer.width() as u32, buffer.height() as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let data: Vec<u8> = buffer.pixels().iter().flat_map(|&c| to_rgb8(c)).collect();
+
+    let mut png_writer = encoder.write_header().map_err(io::Error::other)?;
+    png_writer
+        .write_image_data(&data)
+        .map_err(io::Error::other)?;
+    png_writer.finish().map_err(io::Error::other)
+}
+
+/// Load an image file into a [FrameBuffer] of linear colors
+///
+/// The format is chosen by the file extension of `path`.
+pub fn load_image(path: impl AsRef<Path>) -> io::Result<FrameBuffer> {
+    let path = path.as_ref();
+
+    let format = ImageFormat::from_path(path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unsupported image format: {}", path.display()),
+        )
+    })?;
+
+    let data = fs::read(path)?;
+
+    match format {
+        ImageFormat::Ppm => read_ppm(&data),
+        #[cfg(feature = "png")]
+        ImageFormat::Png => read_png(&data),
+    }
+}
+
+/// Read a plain text (P3) or binary (P6) PPM image
+pub fn read_ppm(data: &[u8]) -> io::Result<FrameBuffer> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, format!("PPM: {}", msg));
+
+    let mut pos = 0;
+
+    // read the next whitespace separated header token, skipping comments
+    let mut token = || -> io::Result<&[u8]> {
+        loop {
+            while pos < data.len() && data[pos].is_ascii_whitespace() {
+                pos += 1;
+            }
+
+            if pos < data.len() && data[pos] == b'#' {
+                while pos < data.len() && data[pos] != b'\n' {
+                    pos += 1;
+                }
+                continue;
+            }
+
+            break;
+        }
+
+        let start = pos;
+        while pos < data.len() && !data[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+
+        if start == pos {
+            return Err(invalid("unexpected end of file"));
+        }
+
+        Ok(&data[start..pos])
+    };
+
+    let parse_num = |bytes: &[u8]| -> io::Result<usize> {
+        std::str::from_utf8(bytes)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| invalid("invalid number"))
+    };
+
+    let magic = token()?;
+    let binary = match magic {
+        b"P3" => false,
+        b"P6" => true,
+        _ => return Err(invalid("only P3 and P6 images are supported")),
+    };
+
+    let width = parse_num(token()?)?;
+    let height = parse_num(token()?)?;
+    let max_val = parse_num(token()?)?;
+
+    if max_val == 0 || max_val > 255 {
+        return Err(invalid("only 8-bit images are supported"));
+    }
+
+    // every value takes at least one byte, so a larger header is invalid (and might overflow)
+    let count = width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(3))
+        .filter(|&count| count <= data.len())
+        .ok_or_else(|| invalid("image is larger than its data"))?;
+
+    let values: Vec<usize> = if binary {
+        // exactly one whitespace character separates the header from the data
+        let start = pos + 1;
+        let bytes = data
+            .get(start..start + count)
+            .ok_or_else(|| invalid("unexpected end of file"))?;
+        bytes.iter().map(|&b| b as usize).collect()
+    } else {
+        (0..count)
+            .map(|_| parse_num(token()?))
+            .collect::<io::Result<_>>()?
+    };
+
+    let pixels = values
+        .chunks(3)
+        .map(|rgb| {
+            let channel = |x: usize| ((x.min(max_val) * 255) / max_val) as u8;
+            from_rgb8([channel(rgb[0]), channel(rgb[1]), channel(rgb[2])])
+        })
+        .collect();
+
+    Ok(FrameBuffer::from_pixels(width, height, pixels))
+}
+
+/// Read an 8-bit RGB or RGBA PNG image
+#[cfg(feature = "png")]
+pub fn read_png(data: &[u8]) -> io::Result<FrameBuffer> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+
+    let mut reader = decoder.read_info().map_err(io::Error::other)?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(io::Error::other)?;
+
+    let channels = info.color_type.samples();
+    let pixels = buf[..info.buffer_size()]
+        .chunks(channels)
+        .map(|px| match px.len() {
+            1 | 2 => from_rgb8([px[0], px[0], px[0]]),
+            _ => from_rgb8([px[0], px[1], px[2]]),
+        })
+        .collect();
+
+    Ok(FrameBuffer::from_pixels(
+        info.width as usize,
+        info.height as usize,
+        pixels,
+    ))
+}
+
+/// Convert 8-bit values to a linear color by undoing the gamma correction
+fn from_rgb8([r, g, b]: [u8; 3]) -> Color {
+    Color::from_u8(r, g, b).correct(1.0 / GAMMA, 1)
+}
+
+/// Gamma correct a linear color and convert it to 8-bit values
+fn to_rgb8(color: Color) -> [u8; 3] {
+    let color = color.correct(GAMMA, 1);
+
+    [
+        (color.r * 255.999) as u8,
+        (color.g * 255.999) as u8,
+        (color.b * 255.999) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{read_ppm, write_ppm, ImageFormat};
+    use crate::color::Color;
+    use crate::render::FrameBuffer;
+
+    fn test_buffer() -> FrameBuffer {
+        let pixels = (0..6)
+            .map(|i| Color::new(i as f64 / 5.0, 1.0 - i as f64 / 5.0, 0.5))
+            .collect();
+
+        FrameBuffer::from_pixels(3, 2, pixels)
+    }
+
+    fn to_ppm(buffer: &FrameBuffer) -> Vec<u8> {
+        let mut data = Vec::new();
+        write_ppm(buffer, &mut data).unwrap();
+        data
+    }
+
+    fn assert_invalid(data: &[u8]) {
+        let err = read_ppm(data).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{}", err);
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert_eq!(ImageFormat::from_path("out.ppm"), Some(ImageFormat::Ppm));
+        assert_eq!(ImageFormat::from_path("OUT.PPM"), Some(ImageFormat::Ppm));
+        assert_eq!(ImageFormat::from_path("out.txt"), None);
+        assert_eq!(ImageFormat::from_path("out"), None);
+    }
+
+    #[test]
+    fn write_ppm_header() {
+        let data = String::from_utf8(to_ppm(&test_buffer())).unwrap();
+        let lines: Vec<_> = data.lines().collect();
+
+        assert_eq!(lines[..3], ["P3", "3 2", "255"]);
+        assert_eq!(lines.len(), 3 + 6);
+        assert_eq!(lines[3], "0 255 181");
+    }
+
+    #[test]
+    fn ppm_round_trip() {
+        let buffer = test_buffer();
+        let data = to_ppm(&buffer);
+        let read = read_ppm(&data).unwrap();
+
+        assert_eq!((read.width(), read.height()), (3, 2));
+        for (a, b) in buffer.pixels().iter().zip(read.pixels()) {
+            assert!((a.r - b.r).abs() < 0.01 && (a.g - b.g).abs() < 0.01);
+        }
+
+        // quantized colors survive another round trip unchanged
+        assert_eq!(to_ppm(&read), data);
+    }
+
+    #[test]
+    fn read_binary_ppm_with_comments() {
+        let mut data = b"P6\n# comment\n2 1 # another comment\n255\n".to_vec();
+        data.extend([255, 0, 0, 0, 0, 255]);
+
+        let buffer = read_ppm(&data).unwrap();
+
+        assert_eq!(buffer.get(0, 0), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(buffer.get(1, 0), Color::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn reject_unsupported_formats() {
+        assert_invalid(b"P5\n1 1\n255\n0");
+        assert_invalid(b"P3\n1 1\n0\n0 0 0");
+        assert_invalid(b"P3\n1 1\n65535\n0 0 0");
+    }
+
+    #[test]
+    fn reject_malformed_headers() {
+        assert_invalid(b"");
+        assert_invalid(b"P3\n");
+        assert_invalid(b"P3\n1\n");
+        assert_invalid(b"P3\n-1 1\n255\n0 0 0");
+        assert_invalid(b"P3\nx 1\n255\n0 0 0");
+    }
+
+    #[test]
+    fn reject_sizes_larger_than_the_data() {
+        assert_invalid(b"P3\n18446744073709551615 2 255\n0 0 0");
+        assert_invalid(b"P6\n6148914691236517206 1 255\n\0\0\0");
+        assert_invalid(b"P6\n100000 100000 255\n\0\0\0");
+        assert_invalid(b"P3\n2 1\n255\n0 0 0 0 0");
+        assert_invalid(b"P6\n2 1\n255\n\0\0\0\0\0");
+    }
+}
@@ -0,0 +1,456 @@
+//! Output rendering
+
+use std::io;
+use std::mem::ManuallyDrop;
+
+use terminal::Terminal;
+use terminal::Value;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+
+use crate::camera::{Camera, CameraView};
+use crate::color::Color;
+use crate::ray::Ray;
+use crate::scene::hittable::Hittable;
+use crate::scene::Scene;
+use crate::scene::SceneBackground;
+
+use cell::Cell;
+use geometry::GeometryBuffer;
+
+pub use accumulator::Accumulator;
+pub use color_depth::ColorDepth;
+pub use framebuffer::FrameBuffer;
+pub use palette::{Background, Palette};
+pub use screen::Screen;
+
+mod accumulator;
+mod ansi;
+mod cell;
+mod color_depth;
+mod framebuffer;
+mod geometry;
+pub mod image;
+mod palette;
+mod screen;
+
+/// Ratio of width to height of a terminal cell
+const TERM_CELL_ASPECT_RATIO: f64 = 0.5;
+
+/// Dimensions/size of the rendered output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderDimensions {
+    ConcreteSize {
+        cols: u16,
+        rows: u16,
+    },
+    #[default]
+    TermSize,
+    RelativeToTermSize {
+        offset_cols: i32,
+        offset_rows: i32,
+    },
+}
+
+/// Mode of the rendered output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    /// Characters of different brightness without color
+    Brightness,
+    /// Colored characters of the same brightness
+    Color,
+    /// Colored characters of different brightness
+    #[default]
+    ColorAndBrightness,
+    /// Upper half blocks with separate foreground and background colors (two pixels per cell)
+    HalfBlock,
+    /// Colored Braille patterns (2x4 dots per cell)
+    Braille,
+    /// Colored characters of different brightness with directional glyphs along edges
+    Edges,
+}
+
+impl RenderMode {
+    /// Return the number of pixels per character cell in horizontal and vertical direction
+    pub fn pixels_per_cell(self) -> (usize, usize) {
+        match self {
+            RenderMode::Brightness
+            | RenderMode::Color
+            | RenderMode::ColorAndBrightness
+            | RenderMode::Edges => (1, 1),
+            RenderMode::HalfBlock => (1, 2),
+            RenderMode::Braille => (2, 4),
+        }
+    }
+
+    /// Return the mode to use for text without colors
+    ///
+    /// Modes that only express colors fall back to [RenderMode::Brightness].
+    pub fn without_colors(self) -> Self {
+        match self {
+            RenderMode::Color | RenderMode::HalfBlock => RenderMode::Brightness,
+            mode => mode,
+        }
+    }
+}
+
+/// Settings that control how a frame is written to the terminal
+#[derive(Debug, Clone, Default)]
+pub struct OutputSettings {
+    /// Size of the output in characters
+    pub dimensions: RenderDimensions,
+    /// What the characters of the output express
+    pub mode: RenderMode,
+    /// Characters that express the brightness of pixels
+    pub palette: Palette,
+    /// Colors the terminal supports
+    pub color_depth: ColorDepth,
+    /// Whether colors are dithered when the color depth is reduced
+    pub dither: bool,
+}
+
+impl OutputSettings {
+    /// Default shorthand constructor (for true color terminals)
+    pub fn new(mode: RenderMode, palette: Palette) -> Self {
+        OutputSettings {
+            mode,
+            palette,
+            ..OutputSettings::default()
+        }
+    }
+}
+
+/// Settings that control the ray tracing of a frame
+#[derive(Debug, Clone)]
+pub struct RenderSettings {
+    /// Maximum number of times a ray bounces off objects
+    pub max_depth: usize,
+    /// Number of rays that are averaged per pixel
+    pub samples_per_pixel: usize,
+    /// Ratio of width to height of a single pixel
+    pub pixel_aspect_ratio: f64,
+    /// Seed of the random numbers (equal seeds give identical frames)
+    pub seed: u64,
+}
+
+impl RenderSettings {
+    /// Default shorthand constructor (for square pixels)
+    pub fn new(max_depth: usize, samples_per_pixel: usize) -> Self {
+        RenderSettings {
+            max_depth,
+            samples_per_pixel,
+            pixel_aspect_ratio: 1.0,
+            seed: 0,
+        }
+    }
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings::new(15, 10)
+    }
+}
+
+/// Main render function that composes a scene and a camera and outputs an image to the terminal
+pub fn render(
+    term: &mut Terminal<io::Stdout>,
+    scene: &Scene,
+    cam: &Camera,
+    settings: &RenderSettings,
+    output: &OutputSettings,
+) -> terminal::error::Result<()> {
+    let (cols, rows) = term_dimensions(term, output.dimensions)?;
+    let settings = term_settings(settings, output.mode);
+    let (width, height) = term_pixels(cols, rows, output.mode);
+
+    let buffer = render_to_buffer(scene, cam, width, height, &settings);
+    let geometry = term_geometry(scene, cam, width, height, &settings, output.mode);
+
+    Screen::new().draw(term, cell::to_cells(&buffer, geometry.as_ref(), output))
+}
+
+/// Render function like [render] that adds the new frame to an [Accumulator]
+///
+/// While the camera stands still, the output converges to a clean image.
+/// Only the cells that differ from the previous frame on the [Screen] are written.
+pub fn render_progressive(
+    term: &mut Terminal<io::Stdout>,
+    screen: &mut Screen,
+    acc: &mut Accumulator,
+    scene: &Scene,
+    cam: &Camera,
+    settings: &RenderSettings,
+    output: &OutputSettings,
+) -> terminal::error::Result<()> {
+    let (cols, rows) = term_dimensions(term, output.dimensions)?;
+    let settings = term_settings(settings, output.mode);
+    let (width, height) = term_pixels(cols, rows, output.mode);
+
+    let buffer = acc.accumulate(scene, cam, width, height, &settings);
+    let geometry = term_geometry(scene, cam, width, height, &settings, output.mode);
+
+    screen.draw(term, cell::to_cells(&buffer, geometry.as_ref(), output))
+}
+
+/// Render a scene seen by a camera as `rows` lines of `cols` characters without colors
+///
+/// The lines are separated by `\n`. Modes that only express colors
+/// are rendered [without colors][RenderMode::without_colors].
+pub fn render_to_text(
+    scene: &Scene,
+    cam: &Camera,
+    cols: u16,
+    rows: u16,
+    settings: &RenderSettings,
+    output: &OutputSettings,
+) -> String {
+    let output = OutputSettings {
+        mode: output.mode.without_colors(),
+        ..output.clone()
+    };
+
+    render_cells(scene, cam, cols, rows, settings, &output)
+        .iter()
+        .map(|cells| cells.iter().map(|cell| cell.ch).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Like [render_to_text] but with the colors set by ANSI escape sequences
+pub fn render_to_ansi(
+    scene: &Scene,
+    cam: &Camera,
+    cols: u16,
+    rows: u16,
+    settings: &RenderSettings,
+    output: &OutputSettings,
+) -> String {
+    ansi::cells_to_ansi(&render_cells(scene, cam, cols, rows, settings, output))
+}
+
+/// Render a scene to `rows` rows of `cols` cells
+fn render_cells(
+    scene: &Scene,
+    cam: &Camera,
+    cols: u16,
+    rows: u16,
+    settings: &RenderSettings,
+    output: &OutputSettings,
+) -> Vec<Vec<Cell>> {
+    let settings = term_settings(settings, output.mode);
+    let (width, height) = term_pixels(cols, rows, output.mode);
+
+    let buffer = render_to_buffer(scene, cam, width, height, &settings);
+    let geometry = term_geometry(scene, cam, width, height, &settings, output.mode);
+
+    cell::to_cells(&buffer, geometry.as_ref(), output)
+}
+
+/// Smallest number of columns and rows of the output
+const MIN_TERM_SIZE: u16 = 2;
+
+/// Resolve the dimensions of the output to columns and rows without drawing to the terminal
+pub fn text_dimensions(dimensions: RenderDimensions) -> terminal::error::Result<(u16, u16)> {
+    // the backend leaves the alternate screen when it is dropped, which would end up in the output
+    let term = ManuallyDrop::new(terminal::stdout());
+    term_dimensions(&term, dimensions)
+}
+
+/// Resolve the dimensions of the output to columns and rows (at least [MIN_TERM_SIZE] each)
+fn term_dimensions(
+    term: &Terminal<io::Stdout>,
+    dimensions: RenderDimensions,
+) -> terminal::error::Result<(u16, u16)> {
+    let term_size = || -> terminal::error::Result<(u16, u16)> {
+        match term.get(Value::TerminalSize)? {
+            terminal::Retrieved::TerminalSize(cols, rows) => Ok((cols, rows)),
+            _ => panic!("Could not get terminal size"),
+        }
+    };
+
+    let (cols, rows) = match dimensions {
+        RenderDimensions::ConcreteSize { cols, rows } => (cols as i32, rows as i32),
+        RenderDimensions::TermSize => {
+            let (cols, rows) = term_size()?;
+            (cols as i32, rows as i32)
+        }
+        RenderDimensions::RelativeToTermSize {
+            offset_cols,
+            offset_rows,
+        } => {
+            let (cols, rows) = term_size()?;
+            (
+                (cols as i32).saturating_add(offset_cols),
+                (rows as i32).saturating_add(offset_rows),
+            )
+        }
+    };
+
+    let clamp = |x: i32| x.clamp(MIN_TERM_SIZE as i32, u16::MAX as i32) as u16;
+
+    Ok((clamp(cols), clamp(rows)))
+}
+
+/// Adapt the settings to the shape of the pixels within terminal cells
+fn term_settings(settings: &RenderSettings, mode: RenderMode) -> RenderSettings {
+    let (cell_width, cell_height) = mode.pixels_per_cell();
+
+    RenderSettings {
+        pixel_aspect_ratio: TERM_CELL_ASPECT_RATIO * cell_height as f64 / cell_width as f64,
+        ..settings.clone()
+    }
+}
+
+/// Return the number of pixels that fill `cols` x `rows` terminal cells
+fn term_pixels(cols: u16, rows: u16, mode: RenderMode) -> (usize, usize) {
+    let (cell_width, cell_height) = mode.pixels_per_cell();
+    (cols as usize * cell_width, rows as usize * cell_height)
+}
+
+/// Trace the geometry of the pixels if the mode needs it
+fn term_geometry(
+    scene: &Scene,
+    cam: &Camera,
+    width: usize,
+    height: usize,
+    settings: &RenderSettings,
+    mode: RenderMode,
+) -> Option<GeometryBuffer> {
+    (mode == RenderMode::Edges)
+        .then(|| geometry::render_geometry(scene, cam, width, height, settings))
+}
+
+/// Render a scene seen by a camera into a [FrameBuffer] of `width` x `height` pixels
+pub fn render_to_buffer(
+    scene: &Scene,
+    cam: &Camera,
+    width: usize,
+    height: usize,
+    settings: &RenderSettings,
+) -> FrameBuffer {
+    let aspect_ratio = (width as f64 * settings.pixel_aspect_ratio) / height as f64;
+    let view = cam.get_view(aspect_ratio);
+
+    // every row is computed by one task, so no synchronization is needed
+    let mut pixels = vec![Color::black(); width * height];
+
+    pixels
+        .par_chunks_mut(width.max(1))
+        .enumerate()
+        .for_each(|(y, row_pixels)| {
+            // the buffer starts at the top, the viewport at the bottom
+            let row = height - y - 1;
+
+            for (col, pixel) in row_pixels.iter_mut().enumerate() {
+                *pixel = render_pixel(scene, &view, col, row, width, height, settings);
+            }
+        });
+
+    FrameBuffer::from_pixels(width, height, pixels)
+}
+
+/// Like [render_to_buffer] but with every pixel computed by its own task
+/// and written to the buffer through a shared lock
+///
+/// This is how frames used to be rendered, it is kept as the baseline of the benchmark
+/// and only built with the `bench` feature.
+#[cfg(feature = "bench")]
+pub fn render_to_buffer_locked(
+    scene: &Scene,
+    cam: &Camera,
+    width: usize,
+    height: usize,
+    settings: &RenderSettings,
+) -> FrameBuffer {
+    use std::sync::{Arc, Mutex};
+
+    let aspect_ratio = (width as f64 * settings.pixel_aspect_ratio) / height as f64;
+    let view = cam.get_view(aspect_ratio);
+
+    let pixels = Arc::new(Mutex::new(vec![Color::black(); width * height]));
+
+    (0..height).into_par_iter().for_each(|y| {
+        (0..width).into_par_iter().for_each(|col| {
+            let row = height - y - 1;
+            let color = render_pixel(scene, &view, col, row, width, height, settings);
+
+            pixels.lock().expect("Could not lock the pixels")[y * width + col] = color;
+        });
+    });
+
+    let pixels = Arc::try_unwrap(pixels)
+        .expect("All tasks have finished")
+        .into_inner()
+        .expect("Could not lock the pixels");
+
+    FrameBuffer::from_pixels(width, height, pixels)
+}
+
+/// Return the average color of the samples of the pixel at `col`, `row` (counted from the bottom)
+fn render_pixel(
+    scene: &Scene,
+    view: &CameraView,
+    col: usize,
+    row: usize,
+    width: usize,
+    height: usize,
+    settings: &RenderSettings,
+) -> Color {
+    let mut color = Color::default();
+    // seeding each pixel makes the frame independent of the order of the pixels,
+    // and unlike `StdRng` the algorithm of ChaCha8 never changes between versions of rand
+    let mut rng = ChaCha8Rng::seed_from_u64(pixel_seed(settings.seed, col, row));
+
+    for _ in 0..settings.samples_per_pixel {
+        let u = (col as f64 + rng.gen::<f64>()) / (width.max(2) - 1) as f64;
+        let v = (row as f64 + rng.gen::<f64>()) / (height.max(2) - 1) as f64;
+        let ray = view.get_ray(u, v, &mut rng);
+        color += ray_color(&ray, scene, settings.max_depth, &mut rng);
+    }
+
+    color / settings.samples_per_pixel as f64
+}
+
+/// Mix the seed of a frame with the position of a pixel into the seed of the pixel
+fn pixel_seed(seed: u64, col: usize, row: usize) -> u64 {
+    // SplitMix64 finalizer, so neighboring pixels get unrelated seeds
+    let mix = |mut x: u64| {
+        x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+        x ^ (x >> 31)
+    };
+
+    mix(mix(mix(seed) ^ col as u64) ^ row as u64)
+}
+
+/// Return the output color of a specific ray
+fn ray_color(ray: &Ray, scene: &Scene, depth: usize, rng: &mut ChaCha8Rng) -> Color {
+    if depth == 0 {
+        return Color::black();
+    }
+
+    if let Some(rec) = scene.objects.hit(ray, 0.001, f64::INFINITY, rng) {
+        let emitted = rec.mat_ptr.emitted(ray, &rec);
+
+        if let Some((attenuation, scattered)) = rec.mat_ptr.scatter(ray, &rec, rng) {
+            return emitted + attenuation * ray_color(&scattered, scene, depth - 1, rng);
+        }
+        return emitted;
+    }
+
+    match scene.background {
+        SceneBackground::Solid(col) => col,
+
+        SceneBackground::VerticalGradient { top, bottom } => {
+            let t = 0.5 * (ray.dir.unit_vec().y + 1.0);
+            (1.0 - t) * bottom + (t * top)
+        }
+
+        SceneBackground::HorizontalGradient { left, right } => {
+            let t = 0.5 * (ray.dir.unit_vec().x + 1.0);
+            (1.0 - t) * left + (t * right)
+        }
+    }
+}
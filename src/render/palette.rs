@@ -0,0 +1,184 @@
+//! Character ramps that express brightness
+
+/// Ramp of 69 ASCII characters with fine brightness steps
+const ASCII_RAMP: &str = ".'`^\",:;Il!i><~+_-?][}{1)(|\\/tfjrxnuvczXYUJCLQ0OZmwqpdbkhao*#MW&8%B@$";
+
+/// Ramp of 10 ASCII characters with coarse brightness steps
+const SHORT_RAMP: &str = " .:-=+*#%@";
+
+/// Ramp of Unicode block elements
+const BLOCK_RAMP: &str = " ░▒▓█";
+
+/// Background color of the terminal that the characters are drawn on
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    /// Dark pixels are drawn with dense characters
+    #[default]
+    Light,
+    /// Bright pixels are drawn with dense characters
+    Dark,
+}
+
+/// Ramp of characters that is used to express the brightness of pixels
+///
+/// The characters are ordered from the least to the most amount of ink.
+/// Which end of the ramp bright pixels get depends on the [Background].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    chars: Vec<char>,
+    background: Background,
+}
+
+impl Palette {
+    /// Constructs a palette from a string of characters ordered from sparse to dense
+    ///
+    /// Return `None` if the string is empty.
+    pub fn new(ramp: &str) -> Option<Self> {
+        let chars: Vec<char> = ramp.chars().collect();
+
+        if chars.is_empty() {
+            return None;
+        }
+
+        Some(Palette {
+            chars,
+            background: Background::default(),
+        })
+    }
+
+    /// Fine-grained ramp of 69 ASCII characters
+    pub fn ascii() -> Self {
+        Palette::from_static(ASCII_RAMP)
+    }
+
+    /// Coarse ramp of 10 ASCII characters
+    pub fn short() -> Self {
+        Palette::from_static(SHORT_RAMP)
+    }
+
+    /// Ramp of the block elements `░▒▓█`
+    pub fn blocks() -> Self {
+        Palette::from_static(BLOCK_RAMP)
+    }
+
+    fn from_static(ramp: &'static str) -> Self {
+        Palette::new(ramp).expect("built-in ramps are not empty")
+    }
+
+    /// Set the background the characters are drawn on
+    pub fn with_background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Return the background the characters are drawn on
+    pub fn background(&self) -> Background {
+        self.background
+    }
+
+    /// Return the characters ordered from sparse to dense
+    pub fn chars(&self) -> &[char] {
+        &self.chars
+    }
+
+    /// Return the character for a brightness in [`0`; `1`]
+    ///
+    /// The brightness range is split into one equally sized interval per character,
+    /// values outside of the range are clamped.
+    pub fn char_for(&self, brightness: f64) -> char {
+        let brightness = if brightness.is_nan() {
+            0.0
+        } else {
+            brightness.clamp(0.0, 1.0)
+        };
+        let density = match self.background {
+            Background::Light => 1.0 - brightness,
+            Background::Dark => brightness,
+        };
+
+        let len = self.chars.len();
+        let idx = ((density * len as f64) as usize).min(len - 1);
+
+        self.chars[idx]
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::ascii()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(background: Background) -> Palette {
+        Palette::new("abcd").unwrap().with_background(background)
+    }
+
+    #[test]
+    fn empty_ramp() {
+        assert_eq!(Palette::new(""), None);
+    }
+
+    #[test]
+    fn light_background() {
+        let palette = ramp(Background::Light);
+
+        assert_eq!(palette.char_for(0.0), 'd');
+        assert_eq!(palette.char_for(0.3), 'c');
+        assert_eq!(palette.char_for(0.6), 'b');
+        assert_eq!(palette.char_for(1.0), 'a');
+    }
+
+    #[test]
+    fn dark_background() {
+        let palette = ramp(Background::Dark);
+
+        assert_eq!(palette.char_for(0.0), 'a');
+        assert_eq!(palette.char_for(0.3), 'b');
+        assert_eq!(palette.char_for(0.6), 'c');
+        assert_eq!(palette.char_for(1.0), 'd');
+    }
+
+    #[test]
+    fn default_matches_baseline() {
+        let palette = Palette::default();
+
+        assert_eq!(palette.background(), Background::Light);
+        assert_eq!(palette.char_for(0.0), '$');
+        assert_eq!(palette.char_for(1.0), '.');
+    }
+
+    #[test]
+    fn equally_sized_intervals() {
+        let palette = ramp(Background::Dark);
+
+        assert_eq!(palette.char_for(0.249), 'a');
+        assert_eq!(palette.char_for(0.25), 'b');
+        assert_eq!(palette.char_for(0.749), 'c');
+        assert_eq!(palette.char_for(0.75), 'd');
+    }
+
+    #[test]
+    fn clamps_out_of_range() {
+        for background in [Background::Light, Background::Dark] {
+            let palette = ramp(background);
+
+            assert_eq!(palette.char_for(-1.0), palette.char_for(0.0));
+            assert_eq!(palette.char_for(2.0), palette.char_for(1.0));
+            assert_eq!(palette.char_for(f64::INFINITY), palette.char_for(1.0));
+            assert_eq!(palette.char_for(f64::NEG_INFINITY), palette.char_for(0.0));
+            assert_eq!(palette.char_for(f64::NAN), palette.char_for(0.0));
+        }
+    }
+
+    #[test]
+    fn single_character() {
+        let palette = Palette::new("#").unwrap();
+
+        assert_eq!(palette.char_for(0.0), '#');
+        assert_eq!(palette.char_for(1.0), '#');
+    }
+}
@@ -0,0 +1,307 @@
+//! Incremental drawing of frames to the terminal
+
+use std::io;
+use std::io::Write;
+
+use terminal::{Action, Clear, Terminal};
+
+use super::cell::Cell;
+
+/// Colors that are currently set in the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Colors {
+    fg: Option<terminal::Color>,
+    bg: Option<terminal::Color>,
+}
+
+/// Change to the terminal that draws part of a frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Clear,
+    MoveTo(u16, u16),
+    SetFg(terminal::Color),
+    SetBg(terminal::Color),
+    ResetColor,
+    Print(char),
+}
+
+/// Content of the terminal screen
+///
+/// A frame is drawn by comparing it to the frame that is currently on the screen
+/// (the front buffer), so only the cells that changed are written.
+/// Runs of cells with the same colors share one color escape sequence.
+#[derive(Debug, Default)]
+pub struct Screen {
+    front: Vec<Vec<Cell>>,
+}
+
+impl Screen {
+    /// Constructs an empty screen, so the first frame is drawn completely
+    pub fn new() -> Self {
+        Screen::default()
+    }
+
+    /// Forget the content of the screen, so the next frame is drawn completely
+    ///
+    /// This is needed when something else wrote to the terminal.
+    pub fn invalidate(&mut self) {
+        self.front.clear();
+    }
+
+    /// Draw rows of cells, only writing the cells that differ from the current content
+    pub(crate) fn draw(
+        &mut self,
+        term: &mut Terminal<io::Stdout>,
+        back: Vec<Vec<Cell>>,
+    ) -> terminal::error::Result<()> {
+        for op in self.diff(back) {
+            match op {
+                Op::Clear => term.batch(Action::ClearTerminal(Clear::All))?,
+                Op::MoveTo(col, row) => term.batch(Action::MoveCursorTo(col, row))?,
+                Op::SetFg(fg) => term.batch(Action::SetForegroundColor(fg))?,
+                Op::SetBg(bg) => term.batch(Action::SetBackgroundColor(bg))?,
+                Op::ResetColor => term.batch(Action::ResetColor)?,
+                Op::Print(ch) => write!(term, "{}", ch)?,
+            }
+        }
+
+        term.flush_batch()?;
+
+        Ok(())
+    }
+
+    /// Return the operations that turn the current content into `back`, which becomes the current content
+    fn diff(&mut self, back: Vec<Vec<Cell>>) -> Vec<Op> {
+        let mut ops = Vec::new();
+
+        // a frame of a different size is drawn on a clean screen
+        let same_size = self.front.len() == back.len()
+            && self
+                .front
+                .iter()
+                .zip(&back)
+                .all(|(f, b)| f.len() == b.len());
+
+        if !same_size {
+            ops.push(Op::Clear);
+            self.front.clear();
+        }
+
+        let mut colors = Colors::default();
+
+        for (row, cells) in back.iter().enumerate() {
+            let front_row = self.front.get(row);
+
+            // column the cursor is at, if it is in this row
+            let mut cursor = None;
+
+            for (col, cell) in cells.iter().enumerate() {
+                if front_row.is_some_and(|front_row| front_row[col] == *cell) {
+                    continue;
+                }
+
+                if cursor != Some(col) {
+                    ops.push(Op::MoveTo(col as u16, row as u16));
+                }
+
+                colors = set_colors(
+                    &mut ops,
+                    colors,
+                    Colors {
+                        fg: cell.fg,
+                        bg: cell.bg,
+                    },
+                );
+
+                ops.push(Op::Print(cell.ch));
+                cursor = Some(col + 1);
+            }
+        }
+
+        if colors != Colors::default() {
+            ops.push(Op::ResetColor);
+        }
+
+        self.front = back;
+
+        ops
+    }
+}
+
+/// Change the colors of the terminal from `current` to `next` with as few operations as possible
+fn set_colors(ops: &mut Vec<Op>, mut current: Colors, next: Colors) -> Colors {
+    if current == next {
+        return current;
+    }
+
+    // a color can only be unset by resetting both
+    if (current.fg.is_some() && next.fg.is_none()) || (current.bg.is_some() && next.bg.is_none()) {
+        ops.push(Op::ResetColor);
+        current = Colors::default();
+    }
+
+    if let Some(fg) = next.fg.filter(|&fg| current.fg != Some(fg)) {
+        ops.push(Op::SetFg(fg));
+    }
+
+    if let Some(bg) = next.bg.filter(|&bg| current.bg != Some(bg)) {
+        ops.push(Op::SetBg(bg));
+    }
+
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use terminal::Color as Term;
+
+    fn cell(ch: char, fg: Option<Term>) -> Cell {
+        Cell { ch, fg, bg: None }
+    }
+
+    fn plain(rows: &[&str]) -> Vec<Vec<Cell>> {
+        rows.iter()
+            .map(|row| row.chars().map(|ch| cell(ch, None)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn first_frame_is_drawn_completely() {
+        let mut screen = Screen::new();
+
+        assert_eq!(
+            screen.diff(plain(&["ab", "cd"])),
+            vec![
+                Op::Clear,
+                Op::MoveTo(0, 0),
+                Op::Print('a'),
+                Op::Print('b'),
+                Op::MoveTo(0, 1),
+                Op::Print('c'),
+                Op::Print('d'),
+            ]
+        );
+    }
+
+    #[test]
+    fn same_frame_draws_nothing() {
+        let mut screen = Screen::new();
+        screen.diff(plain(&["ab", "cd"]));
+
+        assert_eq!(screen.diff(plain(&["ab", "cd"])), vec![]);
+    }
+
+    #[test]
+    fn only_changed_cells_are_drawn() {
+        let mut screen = Screen::new();
+        screen.diff(plain(&["abcd", "efgh"]));
+
+        assert_eq!(
+            screen.diff(plain(&["aXYd", "efgZ"])),
+            vec![
+                Op::MoveTo(1, 0),
+                Op::Print('X'),
+                Op::Print('Y'),
+                Op::MoveTo(3, 1),
+                Op::Print('Z'),
+            ]
+        );
+    }
+
+    #[test]
+    fn unchanged_cell_moves_the_cursor() {
+        let mut screen = Screen::new();
+        screen.diff(plain(&["abc"]));
+
+        assert_eq!(
+            screen.diff(plain(&["XbY"])),
+            vec![
+                Op::MoveTo(0, 0),
+                Op::Print('X'),
+                Op::MoveTo(2, 0),
+                Op::Print('Y'),
+            ]
+        );
+    }
+
+    #[test]
+    fn color_change_redraws_cell() {
+        let mut screen = Screen::new();
+        screen.diff(plain(&["ab"]));
+
+        let mut back = plain(&["ab"]);
+        back[0][1].fg = Some(Term::Red);
+
+        assert_eq!(
+            screen.diff(back),
+            vec![
+                Op::MoveTo(1, 0),
+                Op::SetFg(Term::Red),
+                Op::Print('b'),
+                Op::ResetColor,
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_share_colors() {
+        let mut screen = Screen::new();
+        let back = vec![vec![
+            cell('a', Some(Term::Red)),
+            cell('b', Some(Term::Red)),
+            cell('c', Some(Term::Blue)),
+            cell('d', None),
+        ]];
+
+        assert_eq!(
+            screen.diff(back),
+            vec![
+                Op::Clear,
+                Op::MoveTo(0, 0),
+                Op::SetFg(Term::Red),
+                Op::Print('a'),
+                Op::Print('b'),
+                Op::SetFg(Term::Blue),
+                Op::Print('c'),
+                Op::ResetColor,
+                Op::Print('d'),
+            ]
+        );
+    }
+
+    #[test]
+    fn resize_clears_and_redraws() {
+        let mut screen = Screen::new();
+        screen.diff(plain(&["ab", "cd"]));
+
+        // more rows
+        let ops = screen.diff(plain(&["ab", "cd", "ef"]));
+        assert_eq!(ops[0], Op::Clear);
+        assert_eq!(
+            ops.iter().filter(|op| matches!(op, Op::Print(_))).count(),
+            6
+        );
+
+        // wider rows
+        let ops = screen.diff(plain(&["abc", "cde", "efg"]));
+        assert_eq!(ops[0], Op::Clear);
+        assert_eq!(
+            ops.iter().filter(|op| matches!(op, Op::Print(_))).count(),
+            9
+        );
+    }
+
+    #[test]
+    fn invalidate_redraws_everything() {
+        let mut screen = Screen::new();
+        screen.diff(plain(&["ab"]));
+        screen.invalidate();
+
+        assert_eq!(
+            screen.diff(plain(&["ab"])),
+            vec![Op::Clear, Op::MoveTo(0, 0), Op::Print('a'), Op::Print('b')]
+        );
+    }
+}
@@ -0,0 +1,1072 @@
+//! Loading scenes from scene description files
+//!
+//! A scene file is a plain text file with one statement per line.
+//! Every statement starts with a keyword, followed by positional words and `key=value` fields.
+//! Empty lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! # background: solid, vertical or horizontal
+//! background vertical top=0.5,0.7,1.0 bottom=#ffffff
+//!
+//! # camera (all fields are optional)
+//! camera pos=0,0,0 lookat=0,0,-1 vup=0,1,0 vfov=90 aperture=0.1 focus_dist=1 shutter=0,1
+//!
+//! # named textures: solid, checker, image, noise, marble or wood
+//! texture checks checker even=0.2,0.3,0.1 odd=0.9,0.9,0.9 scale=0.5
+//! texture earth image file=earthmap.ppm
+//! texture stone marble seed=42 scale=4 vein=0.1,0.1,0.1 base=1,1,1
+//!
+//! # named materials: lambertian, metal, dielectric, light or isotropic
+//! # (lambertian, metal and isotropic take either a color `albedo=` or a texture `texture=`)
+//! material ground lambertian texture=checks
+//! material mirror metal albedo=0,0,1 fuzz=0.8
+//! material glass dielectric ir=1.5
+//! material lamp light emit=4,4,4
+//! material smoke isotropic albedo=0.2,0.2,0.2
+//!
+//! # objects
+//! sphere center=0,-100.5,-1 radius=100 material=ground
+//! rect plane=xz x=3,5 z=1,3 k=2 material=mirror
+//! box min=-1,0,-3 max=1,1,-2 material=ground
+//! sphere center=0,1,-3 radius=0.5 material=smoke density=2
+//! sphere center=1,0,-1 center1=1,0.5,-1 time=0,1 radius=0.2 material=mirror
+//! triangle a=-1,0,-2 b=1,0,-2 c=0,1,-2 material=glass
+//! mesh file=models/bunny.obj material=ground scale=2,2,2 rotate=0,45,0 translate=0,-1,-3
+//! ```
+//!
+//! Every object takes the optional fields `scale=x,y,z`, `rotate=x,y,z` (degrees around each axis)
+//! and `translate=x,y,z`, which are applied in this order.
+//! With `density=` the object becomes the boundary of a constant-density medium (fog or smoke)
+//! that scatters with its material, which should be isotropic.
+//! A sphere with `center1=` moves from `center` to `center1` during `time=` (default `0,1`),
+//! which is blurred if the camera shutter is open in between.
+//!
+//! Colors are either three comma separated values (usually in `[0; 1]`) or a hex code like `#ff8000`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::lalg::Vec3;
+
+use super::hittable::{
+    AaBox, AaRect, AaRectPlaneCoords, ConstantMedium, Hittable, MovingSphere, Sphere, Transform,
+    Triangle, TriangleMesh,
+};
+use super::material::{Dielectric, DiffuseLight, Isotropic, Lambertian, Material, Metal};
+use super::texture::{
+    CheckerTexture, ImageTexture, MarbleTexture, NoiseTexture, SolidColor, Texture, WoodTexture,
+};
+use super::{Scene, SceneBackground};
+
+/// Error that occurs while loading a scene file
+#[derive(Debug)]
+pub enum SceneFileError {
+    /// The file could not be read
+    Io(io::Error),
+    /// The file contents are invalid
+    Parse { line: usize, msg: String },
+}
+
+impl fmt::Display for SceneFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneFileError::Io(err) => write!(f, "Could not read scene file: {}", err),
+            SceneFileError::Parse { line, msg } => write!(f, "line {}: {}", line, msg),
+        }
+    }
+}
+
+impl std::error::Error for SceneFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneFileError::Io(err) => Some(err),
+            SceneFileError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for SceneFileError {
+    fn from(err: io::Error) -> Self {
+        SceneFileError::Io(err)
+    }
+}
+
+impl Scene {
+    /// Load a scene from a scene description file
+    ///
+    /// Relative paths in the file are resolved relative to the directory of the file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Scene, SceneFileError> {
+        let path = path.as_ref();
+        let src = fs::read_to_string(path)?;
+
+        parse_scene(&src, path.parent().unwrap_or_else(|| Path::new("")))
+    }
+}
+
+impl FromStr for Scene {
+    type Err = SceneFileError;
+
+    /// Parse a scene (relative paths are resolved relative to the working directory)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_scene(s, Path::new(""))
+    }
+}
+
+fn parse_scene(s: &str, base_dir: &Path) -> Result<Scene, SceneFileError> {
+    let mut background = None;
+    let mut camera = None;
+    let mut textures: HashMap<String, Box<dyn Texture>> = HashMap::new();
+    let mut materials: HashMap<String, Box<dyn Material>> = HashMap::new();
+    let mut builder = Scene::builder(SceneBackground::Solid(Color::white())).use_bvh(true);
+
+    for (idx, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut stmt = Statement::parse(idx + 1, line)?;
+
+        match stmt.keyword {
+            "background" => {
+                if background.is_some() {
+                    return Err(stmt.error("background is defined more than once"));
+                }
+                background = Some(stmt.background()?);
+            }
+            "camera" => {
+                if camera.is_some() {
+                    return Err(stmt.error("camera is defined more than once"));
+                }
+                camera = Some(stmt.camera()?);
+            }
+            "material" => {
+                let name = stmt.positional(0, "material name")?.to_string();
+                if materials.contains_key(&name) {
+                    return Err(stmt.error(format!("material '{}' is already defined", name)));
+                }
+                let mat = stmt.material(&textures)?;
+                materials.insert(name, mat);
+            }
+            "texture" => {
+                let name = stmt.positional(0, "texture name")?.to_string();
+                if textures.contains_key(&name) {
+                    return Err(stmt.error(format!("texture '{}' is already defined", name)));
+                }
+                let tex = stmt.texture(base_dir)?;
+                textures.insert(name, tex);
+            }
+            "sphere" => {
+                stmt.no_positionals()?;
+                let mat = stmt.material_ref(&materials)?;
+                let center = stmt.vec3("center")?;
+                let radius = stmt.f64("radius")?;
+
+                // a second center makes the sphere move
+                let sphere: Box<dyn Hittable> = match stmt.optional_vec3("center1")? {
+                    Some(center1) => {
+                        let time = stmt.optional_range("time")?.unwrap_or((0.0, 1.0));
+                        MovingSphere::new((center, center1), time, radius, mat.clone()).boxed()
+                    }
+                    None => Box::new(Sphere::new(center, radius, mat.clone())),
+                };
+                builder = builder.add_object(stmt.object(sphere, mat)?);
+            }
+            "box" => {
+                stmt.no_positionals()?;
+                let mat = stmt.material_ref(&materials)?;
+                let aabox = AaBox::new(stmt.vec3("min")?, stmt.vec3("max")?, mat.clone());
+                builder = builder.add_object(stmt.object(aabox.boxed(), mat)?);
+            }
+            "triangle" => {
+                stmt.no_positionals()?;
+                let mat = stmt.material_ref(&materials)?;
+                let triangle = Triangle::new(
+                    stmt.vec3("a")?,
+                    stmt.vec3("b")?,
+                    stmt.vec3("c")?,
+                    mat.clone(),
+                );
+                builder = builder.add_object(stmt.object(triangle.boxed(), mat)?);
+            }
+            "mesh" => {
+                stmt.no_positionals()?;
+                let file = base_dir.join(stmt.take("file")?);
+                let mat = stmt.material_ref(&materials)?;
+                let mesh = TriangleMesh::from_obj(&file, mat.clone()).map_err(|err| {
+                    stmt.error(format!("could not load mesh '{}': {}", file.display(), err))
+                })?;
+                builder = builder.add_object(stmt.object(mesh.boxed(), mat)?);
+            }
+            "rect" => {
+                stmt.no_positionals()?;
+                let mat = stmt.material_ref(&materials)?;
+                let rect = AaRect::new(stmt.plane_coords()?, stmt.f64("k")?, mat.clone());
+                builder = builder.add_object(stmt.object(Box::new(rect), mat)?);
+            }
+            keyword => {
+                return Err(stmt.error(format!("unknown statement '{}'", keyword)));
+            }
+        }
+
+        stmt.finish()?;
+    }
+
+    if let Some(background) = background {
+        builder = builder.background(background);
+    }
+
+    if let Some(camera) = camera {
+        builder = builder.camera(camera);
+    }
+
+    Ok(builder.build())
+}
+
+/// A single line of a scene file
+struct Statement<'a> {
+    line: usize,
+    keyword: &'a str,
+    positionals: Vec<&'a str>,
+    fields: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> Statement<'a> {
+    fn parse(line: usize, s: &'a str) -> Result<Self, SceneFileError> {
+        let mut words = s.split_whitespace();
+        let keyword = words.next().unwrap_or_default();
+
+        let mut stmt = Statement {
+            line,
+            keyword,
+            positionals: Vec::new(),
+            fields: HashMap::new(),
+        };
+
+        for word in words {
+            match word.split_once('=') {
+                Some((key, value)) => {
+                    if key.is_empty() || value.is_empty() {
+                        return Err(stmt.error(format!("invalid field '{}'", word)));
+                    }
+                    if stmt.fields.insert(key, value).is_some() {
+                        return Err(stmt.error(format!("field '{}' is given more than once", key)));
+                    }
+                }
+                None if stmt.fields.is_empty() => stmt.positionals.push(word),
+                None => {
+                    return Err(stmt.error(format!("expected 'key=value', found '{}'", word)));
+                }
+            }
+        }
+
+        Ok(stmt)
+    }
+
+    fn error(&self, msg: impl Into<String>) -> SceneFileError {
+        SceneFileError::Parse {
+            line: self.line,
+            msg: msg.into(),
+        }
+    }
+
+    /// Make sure that every field was used
+    fn finish(self) -> Result<(), SceneFileError> {
+        let mut unused: Vec<&str> = self.fields.keys().copied().collect();
+        unused.sort_unstable();
+
+        match unused.first() {
+            Some(key) => Err(self.error(format!("unknown field '{}' for {}", key, self.keyword))),
+            None => Ok(()),
+        }
+    }
+
+    fn positional(&self, idx: usize, what: &str) -> Result<&'a str, SceneFileError> {
+        self.positionals
+            .get(idx)
+            .copied()
+            .ok_or_else(|| self.error(format!("missing {} for {}", what, self.keyword)))
+    }
+
+    fn no_positionals(&self) -> Result<(), SceneFileError> {
+        self.expect_positionals(0)
+    }
+
+    fn expect_positionals(&self, n: usize) -> Result<(), SceneFileError> {
+        match self.positionals.get(n) {
+            Some(word) => Err(self.error(format!("unexpected '{}'", word))),
+            None => Ok(()),
+        }
+    }
+
+    fn take(&mut self, key: &str) -> Result<&'a str, SceneFileError> {
+        self.take_optional(key)
+            .ok_or_else(|| self.error(format!("missing field '{}' for {}", key, self.keyword)))
+    }
+
+    fn take_optional(&mut self, key: &str) -> Option<&'a str> {
+        self.fields.remove(key)
+    }
+
+    fn f64(&mut self, key: &str) -> Result<f64, SceneFileError> {
+        let value = self.take(key)?;
+        self.parse_f64(key, value)
+    }
+
+    fn optional_f64(&mut self, key: &str) -> Result<Option<f64>, SceneFileError> {
+        self.take_optional(key)
+            .map(|value| self.parse_f64(key, value))
+            .transpose()
+    }
+
+    fn vec3(&mut self, key: &str) -> Result<Vec3, SceneFileError> {
+        let value = self.take(key)?;
+        self.parse_vec3(key, value)
+    }
+
+    fn optional_vec3(&mut self, key: &str) -> Result<Option<Vec3>, SceneFileError> {
+        self.take_optional(key)
+            .map(|value| self.parse_vec3(key, value))
+            .transpose()
+    }
+
+    /// Parse the extent of a rectangle, which must not be empty
+    fn extent(&mut self, key: &str) -> Result<(f64, f64), SceneFileError> {
+        let value = self.take(key)?;
+        let (min, max) = self.parse_range(key, value)?;
+
+        if min == max {
+            return Err(self.error(format!(
+                "invalid range for '{}': {} to {} is empty",
+                key, min, max
+            )));
+        }
+
+        Ok((min, max))
+    }
+
+    fn optional_range(&mut self, key: &str) -> Result<Option<(f64, f64)>, SceneFileError> {
+        self.take_optional(key)
+            .map(|value| self.parse_range(key, value))
+            .transpose()
+    }
+
+    fn parse_range(&self, key: &str, value: &str) -> Result<(f64, f64), SceneFileError> {
+        let parts = self.parse_list(key, value, 2)?;
+        if parts[0] > parts[1] {
+            return Err(self.error(format!(
+                "invalid range for '{}': {} is greater than {}",
+                key, parts[0], parts[1]
+            )));
+        }
+
+        Ok((parts[0], parts[1]))
+    }
+
+    fn color(&mut self, key: &str) -> Result<Color, SceneFileError> {
+        let value = self.take(key)?;
+        self.parse_color(key, value)
+    }
+
+    fn optional_color(&mut self, key: &str) -> Result<Option<Color>, SceneFileError> {
+        self.take_optional(key)
+            .map(|value| self.parse_color(key, value))
+            .transpose()
+    }
+
+    fn parse_color(&self, key: &str, value: &str) -> Result<Color, SceneFileError> {
+        if let Some(hex) = value.strip_prefix('#') {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|c| u8::from_str_radix(c, 16).ok())
+            };
+
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Color::from_u8(r, g, b)),
+                _ => Err(self.error(format!("invalid color for '{}': '{}'", key, value))),
+            };
+        }
+
+        let parts = self.parse_list(key, value, 3)?;
+        Ok(Color::new(parts[0], parts[1], parts[2]))
+    }
+
+    fn parse_f64(&self, key: &str, value: &str) -> Result<f64, SceneFileError> {
+        match value.parse::<f64>() {
+            Ok(x) if x.is_finite() => Ok(x),
+            _ => Err(self.error(format!("invalid number for '{}': '{}'", key, value))),
+        }
+    }
+
+    fn parse_vec3(&self, key: &str, value: &str) -> Result<Vec3, SceneFileError> {
+        let parts = self.parse_list(key, value, 3)?;
+        Ok(Vec3::new(parts[0], parts[1], parts[2]))
+    }
+
+    fn parse_list(&self, key: &str, value: &str, n: usize) -> Result<Vec<f64>, SceneFileError> {
+        let parts = value
+            .split(',')
+            .map(|part| self.parse_f64(key, part))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if parts.len() != n {
+            return Err(self.error(format!(
+                "expected {} comma separated values for '{}', found {}",
+                n,
+                key,
+                parts.len()
+            )));
+        }
+
+        Ok(parts)
+    }
+
+    fn background(&mut self) -> Result<SceneBackground, SceneFileError> {
+        let kind = self.positional(0, "background type")?;
+        self.expect_positionals(1)?;
+
+        match kind {
+            "solid" => Ok(SceneBackground::Solid(self.color("color")?)),
+            "vertical" => Ok(SceneBackground::VerticalGradient {
+                top: self.color("top")?,
+                bottom: self.color("bottom")?,
+            }),
+            "horizontal" => Ok(SceneBackground::HorizontalGradient {
+                left: self.color("left")?,
+                right: self.color("right")?,
+            }),
+            kind => Err(self.error(format!(
+                "unknown background type '{}' (expected solid, vertical or horizontal)",
+                kind
+            ))),
+        }
+    }
+
+    fn camera(&mut self) -> Result<Camera, SceneFileError> {
+        self.no_positionals()?;
+
+        let default = Camera::default();
+        let pos = self.optional_vec3("pos")?.unwrap_or(default.pos());
+        let lookat = self.optional_vec3("lookat")?.unwrap_or(default.lookat());
+        let vup = self.optional_vec3("vup")?.unwrap_or(default.vup());
+        let vfov = self.optional_f64("vfov")?.unwrap_or(default.vfov());
+
+        if (lookat - pos).near_zero() {
+            return Err(self.error("camera 'pos' and 'lookat' must differ"));
+        }
+
+        if vup.near_zero() {
+            return Err(self.error("camera 'vup' must not be zero"));
+        }
+
+        if vfov <= 0.0 || vfov >= 180.0 {
+            return Err(self.error(format!(
+                "invalid value for 'vfov': {} (must be in (0; 180))",
+                vfov
+            )));
+        }
+
+        let mut camera = Camera::new(pos, lookat, vup, vfov);
+
+        if let Some(aperture) = self.optional_f64("aperture")? {
+            if aperture < 0.0 {
+                return Err(self.error(format!(
+                    "invalid value for 'aperture': {} (must not be negative)",
+                    aperture
+                )));
+            }
+            camera.set_aperture(aperture);
+        }
+
+        if let Some(focus_dist) = self.optional_f64("focus_dist")? {
+            if focus_dist <= 0.0 {
+                return Err(self.error(format!(
+                    "invalid value for 'focus_dist': {} (must be positive)",
+                    focus_dist
+                )));
+            }
+            camera.set_focus_dist(focus_dist);
+        }
+
+        if let Some((open, close)) = self.optional_range("shutter")? {
+            camera.set_shutter(open, close);
+        }
+
+        Ok(camera)
+    }
+
+    fn material(
+        &mut self,
+        textures: &HashMap<String, Box<dyn Texture>>,
+    ) -> Result<Box<dyn Material>, SceneFileError> {
+        let kind = self.positional(1, "material type")?;
+        self.expect_positionals(2)?;
+
+        match kind {
+            "lambertian" => Ok(Lambertian::textured(self.albedo(textures)?).boxed()),
+            "metal" => {
+                let albedo = self.albedo(textures)?;
+                let fuzz = self.optional_f64("fuzz")?.unwrap_or(0.0);
+                Ok(Metal::textured(albedo, fuzz).boxed())
+            }
+            "dielectric" => Ok(Dielectric::new(self.f64("ir")?).boxed()),
+            "light" => Ok(DiffuseLight::new(self.color("emit")?).boxed()),
+            "isotropic" => Ok(Isotropic::textured(self.albedo(textures)?).boxed()),
+            kind => Err(self.error(format!(
+                "unknown material type '{}' (expected lambertian, metal, dielectric, light or isotropic)",
+                kind
+            ))),
+        }
+    }
+
+    /// Read either a color (`albedo=`) or a reference to a named texture (`texture=`)
+    fn albedo(
+        &mut self,
+        textures: &HashMap<String, Box<dyn Texture>>,
+    ) -> Result<Box<dyn Texture>, SceneFileError> {
+        match self.take_optional("texture") {
+            Some(_) if self.fields.contains_key("albedo") => {
+                Err(self
+                    .error("fields 'albedo' and 'texture' can not be used together".to_string()))
+            }
+            Some(name) => textures
+                .get(name)
+                .cloned()
+                .ok_or_else(|| self.error(format!("unknown texture '{}'", name))),
+            None => Ok(self.color("albedo")?.into()),
+        }
+    }
+
+    /// Read the optional `seed` and `scale` of procedural noise textures
+    fn noise_params(&mut self) -> Result<(u64, f64), SceneFileError> {
+        let seed = match self.take_optional("seed") {
+            Some(value) => value
+                .parse()
+                .map_err(|_| self.error(format!("invalid value for 'seed': '{}'", value)))?,
+            None => 0,
+        };
+
+        let scale = self.optional_f64("scale")?.unwrap_or(1.0);
+        if scale <= 0.0 {
+            return Err(self.error(format!(
+                "invalid value for 'scale': {} (must be positive)",
+                scale
+            )));
+        }
+
+        Ok((seed, scale))
+    }
+
+    fn texture(&mut self, base_dir: &Path) -> Result<Box<dyn Texture>, SceneFileError> {
+        let kind = self.positional(1, "texture type")?;
+        self.expect_positionals(2)?;
+
+        match kind {
+            "solid" => Ok(SolidColor::new(self.color("color")?).boxed()),
+            "checker" => {
+                let even = self.color("even")?;
+                let odd = self.color("odd")?;
+                let scale = self.optional_f64("scale")?.unwrap_or(1.0);
+                if scale <= 0.0 {
+                    return Err(self.error(format!(
+                        "invalid value for 'scale': {} (must be positive)",
+                        scale
+                    )));
+                }
+                Ok(CheckerTexture::from_colors(even, odd, scale).boxed())
+            }
+            "image" => {
+                let file = base_dir.join(self.take("file")?);
+                let tex = ImageTexture::from_file(&file).map_err(|err| {
+                    self.error(format!(
+                        "could not load image '{}': {}",
+                        file.display(),
+                        err
+                    ))
+                })?;
+                Ok(tex.boxed())
+            }
+            "noise" => {
+                let (seed, scale) = self.noise_params()?;
+                let low = self.optional_color("low")?.unwrap_or_else(Color::black);
+                let high = self.optional_color("high")?.unwrap_or_else(Color::white);
+                Ok(NoiseTexture::with_colors(seed, scale, low, high).boxed())
+            }
+            "marble" => {
+                let (seed, scale) = self.noise_params()?;
+                let default = MarbleTexture::new(seed, scale);
+                let vein = self.optional_color("vein")?.unwrap_or(default.vein);
+                let base = self.optional_color("base")?.unwrap_or(default.base);
+                Ok(MarbleTexture::with_colors(seed, scale, vein, base).boxed())
+            }
+            "wood" => {
+                let (seed, scale) = self.noise_params()?;
+                let default = WoodTexture::new(seed, scale);
+                let ring = self.optional_color("ring")?.unwrap_or(default.ring);
+                let base = self.optional_color("base")?.unwrap_or(default.base);
+                Ok(WoodTexture::with_colors(seed, scale, ring, base).boxed())
+            }
+            kind => Err(self.error(format!(
+                "unknown texture type '{}' (expected solid, checker, image, noise, marble or wood)",
+                kind
+            ))),
+        }
+    }
+
+    fn material_ref(
+        &mut self,
+        materials: &HashMap<String, Box<dyn Material>>,
+    ) -> Result<Box<dyn Material>, SceneFileError> {
+        let name = self.take("material")?;
+
+        materials
+            .get(name)
+            .cloned()
+            .ok_or_else(|| self.error(format!("unknown material '{}'", name)))
+    }
+
+    /// Apply the optional transformation and medium fields to an object
+    fn object(
+        &mut self,
+        obj: Box<dyn Hittable>,
+        mat: Box<dyn Material>,
+    ) -> Result<Box<dyn Hittable>, SceneFileError> {
+        let obj = self.transformed(obj)?;
+
+        match self.optional_f64("density")? {
+            Some(density) if density <= 0.0 => Err(self.error(format!(
+                "invalid value for 'density': {} (must be positive)",
+                density
+            ))),
+            Some(density) => Ok(ConstantMedium::with_phase_function(obj, density, mat).boxed()),
+            None => Ok(obj),
+        }
+    }
+
+    /// Wrap an object in a [Transform] if any transformation fields are given
+    fn transformed(&mut self, obj: Box<dyn Hittable>) -> Result<Box<dyn Hittable>, SceneFileError> {
+        let scale = self.optional_vec3("scale")?;
+        let rotate = self.optional_vec3("rotate")?;
+        let translate = self.optional_vec3("translate")?;
+
+        if scale.is_none() && rotate.is_none() && translate.is_none() {
+            return Ok(obj);
+        }
+
+        let mut transform = Transform::new(obj);
+
+        if let Some(scale) = scale {
+            if scale.x == 0.0 || scale.y == 0.0 || scale.z == 0.0 {
+                return Err(self.error("invalid value for 'scale': factors must not be zero"));
+            }
+            transform = transform.scale(scale);
+        }
+
+        if let Some(rotate) = rotate {
+            transform = transform
+                .rotate_x(rotate.x)
+                .rotate_y(rotate.y)
+                .rotate_z(rotate.z);
+        }
+
+        if let Some(translate) = translate {
+            transform = transform.translate(translate);
+        }
+
+        Ok(transform.boxed())
+    }
+
+    fn plane_coords(&mut self) -> Result<AaRectPlaneCoords, SceneFileError> {
+        let plane = self.take("plane")?;
+
+        match plane {
+            "xy" => Ok(AaRectPlaneCoords::Xy {
+                x: self.extent("x")?,
+                y: self.extent("y")?,
+            }),
+            "xz" => Ok(AaRectPlaneCoords::Xz {
+                x: self.extent("x")?,
+                z: self.extent("z")?,
+            }),
+            "yz" => Ok(AaRectPlaneCoords::Yz {
+                y: self.extent("y")?,
+                z: self.extent("z")?,
+            }),
+            plane => Err(self.error(format!(
+                "invalid value for 'plane': '{}' (expected xy, xz or yz)",
+                plane
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::SceneFileError;
+    use crate::color::Color;
+    use crate::lalg::{Point3, Vec3};
+    use crate::ray::Ray;
+    use crate::scene::hittable::{test_rng, HitRecord, Hittable};
+    use crate::scene::{Scene, SceneBackground};
+
+    const MATERIALS: &str = "
+material white lambertian albedo=1,1,1
+";
+
+    fn parse(src: &str) -> Scene {
+        src.parse()
+            .unwrap_or_else(|err| panic!("could not parse scene: {}", err))
+    }
+
+    /// Return the line and message of the error of an invalid scene
+    fn parse_error(src: &str) -> (usize, String) {
+        match src.parse::<Scene>() {
+            Ok(_) => panic!("scene is valid:\n{}", src),
+            Err(SceneFileError::Parse { line, msg }) => (line, msg),
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+    }
+
+    /// Return the message of the error of an invalid object (defined after [MATERIALS])
+    fn object_error(object: &str) -> String {
+        parse_error(&format!("{}{}", MATERIALS, object)).1
+    }
+
+    /// Shoot a ray from `origin` towards negative z
+    fn hit(scene: &Scene, origin: (f64, f64, f64)) -> Option<HitRecord> {
+        let ray = Ray::new(origin.into(), Vec3::new(0.0, 0.0, -1.0));
+        scene
+            .objects
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+    }
+
+    #[test]
+    fn empty_scene() {
+        let scene = parse("# only a comment\n\n   \n");
+
+        assert!(scene.camera.is_none());
+        assert!(matches!(scene.background, SceneBackground::Solid(c) if c == Color::white()));
+        assert!(hit(&scene, (0.0, 0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn backgrounds() {
+        let scene = parse("background solid color=#ff0000");
+        assert!(
+            matches!(scene.background, SceneBackground::Solid(c) if c == Color::new(1.0, 0.0, 0.0))
+        );
+
+        let scene = parse("background vertical top=0,0,1 bottom=1,1,1");
+        assert!(matches!(
+            scene.background,
+            SceneBackground::VerticalGradient { top, bottom }
+                if top == Color::new(0.0, 0.0, 1.0) && bottom == Color::white()
+        ));
+
+        let scene = parse("background horizontal left=0,0,0 right=#FFFFFF");
+        assert!(matches!(
+            scene.background,
+            SceneBackground::HorizontalGradient { left, right }
+                if left == Color::black() && right == Color::white()
+        ));
+    }
+
+    #[test]
+    fn camera() {
+        let scene = parse(
+            "camera pos=1,2,3 lookat=0,0,-1 vup=0,0,1 vfov=45 aperture=0.5 focus_dist=2 shutter=0.25,0.75",
+        );
+        let cam = scene.camera.unwrap();
+
+        assert_eq!(cam.pos(), Point3::new(1.0, 2.0, 3.0));
+        assert_eq!(cam.lookat(), Point3::new(0.0, 0.0, -1.0));
+        assert_eq!(cam.vfov(), 45.0);
+        assert_eq!(cam.aperture(), 0.5);
+        assert_eq!(cam.focus_dist(), 2.0);
+        assert_eq!((cam.shutter_open(), cam.shutter_close()), (0.25, 0.75));
+    }
+
+    #[test]
+    fn camera_defaults() {
+        let cam = parse("camera vfov=30").camera.unwrap();
+
+        assert_eq!(cam.vfov(), 30.0);
+        assert_eq!(cam.pos(), crate::camera::Camera::default().pos());
+    }
+
+    #[test]
+    fn materials() {
+        let scene = parse(
+            "
+texture checks checker even=0,0,0 odd=1,1,1 scale=2
+material a lambertian albedo=0.5,0.5,0.5
+material b lambertian texture=checks
+material c metal albedo=0.8,0.8,0.8 fuzz=0.3
+material d dielectric ir=1.5
+material e light emit=4,4,4
+material f isotropic albedo=0.2,0.2,0.2
+sphere center=0,0,-2 radius=0.5 material=c
+",
+        );
+
+        let rec = hit(&scene, (0.0, 0.0, 0.0)).unwrap();
+        assert!(format!("{:?}", rec.mat_ptr).starts_with("Metal"));
+    }
+
+    #[test]
+    fn textures() {
+        parse(
+            "
+texture a solid color=1,0,0
+texture b checker even=0,0,0 odd=1,1,1
+texture c noise seed=3 scale=2 low=0,0,0 high=1,1,1
+texture d marble seed=4 vein=0,0,0 base=1,1,1
+texture e wood scale=0.5 ring=0,0,0 base=1,1,1
+texture f image file=scenes/models/stripes.ppm
+",
+        );
+    }
+
+    #[test]
+    fn objects() {
+        let scene = parse(&format!(
+            "{}
+sphere center=0,0,-2 radius=0.5 material=white
+sphere center=2,0,-2 center1=2,1,-2 time=0,1 radius=0.5 material=white
+box min=3.5,-0.5,-3 max=4.5,0.5,-2 material=white
+triangle a=5.5,-0.5,-2 b=6.5,-0.5,-2 c=6,0.5,-2 material=white
+rect plane=xy x=7.5,8.5 y=-0.5,0.5 k=-2 material=white
+mesh file=scenes/models/octahedron.obj material=white translate=10,0,-2
+",
+            MATERIALS
+        ));
+
+        for (x, t) in [(0.0, 1.5), (2.0, 1.5), (4.0, 2.0), (6.0, 2.0), (8.0, 2.0)] {
+            let rec = hit(&scene, (x, 0.0, 0.0)).unwrap_or_else(|| panic!("no object at {}", x));
+            assert!((rec.t - t).abs() < 1e-9, "object at {} hit at {}", x, rec.t);
+        }
+
+        assert!(hit(&scene, (10.0, 0.0, 0.0)).is_some());
+        assert!(hit(&scene, (-2.0, 0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn transformed_object() {
+        let scene = parse(&format!(
+            "{}sphere center=0,0,0 radius=1 material=white scale=0.5,0.5,0.5 rotate=0,90,0 translate=3,0,-2",
+            MATERIALS
+        ));
+
+        let rec = hit(&scene, (3.0, 0.0, 0.0)).unwrap();
+        assert!((rec.t - 1.5).abs() < 1e-9);
+        assert!(hit(&scene, (0.0, 0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn medium() {
+        let scene = parse(
+            "
+material smoke isotropic albedo=0.2,0.2,0.2
+sphere center=0,0,-2 radius=1 material=smoke density=1000
+",
+        );
+
+        let rec = hit(&scene, (0.0, 0.0, 0.0)).unwrap();
+        assert!(rec.t > 1.0 && rec.t < 1.1);
+        assert!(format!("{:?}", rec.mat_ptr).starts_with("Isotropic"));
+    }
+
+    #[test]
+    fn bundled_scenes() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes");
+
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "scene") {
+                if let Err(err) = Scene::from_file(&path) {
+                    panic!("{}: {}", path.display(), err);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn missing_file() {
+        let err = Scene::from_file("does/not/exist.scene").unwrap_err();
+        assert!(matches!(err, SceneFileError::Io(_)));
+    }
+
+    #[test]
+    fn error_includes_line_number() {
+        let src = "# comment\n\nbackground solid color=1,1,1\nsphere center=0,0,0 radius=1 material=nope\n";
+        let err = src.parse::<Scene>().unwrap_err();
+
+        assert_eq!(err.to_string(), "line 4: unknown material 'nope'");
+    }
+
+    #[test]
+    fn unknown_statements_and_fields() {
+        assert_eq!(
+            parse_error("cube size=1"),
+            (1, "unknown statement 'cube'".to_string())
+        );
+        assert_eq!(
+            object_error("sphere center=0,0,0 radius=1 material=white color=1,0,0"),
+            "unknown field 'color' for sphere"
+        );
+        assert_eq!(
+            parse_error("camera fov=90").1,
+            "unknown field 'fov' for camera"
+        );
+        assert_eq!(
+            parse_error("background gradient top=1,1,1").1,
+            "unknown background type 'gradient' (expected solid, vertical or horizontal)"
+        );
+        assert_eq!(
+            parse_error("material m plastic").1,
+            "unknown material type 'plastic' (expected lambertian, metal, dielectric, light or isotropic)"
+        );
+        assert_eq!(
+            parse_error("texture t stripes").1,
+            "unknown texture type 'stripes' (expected solid, checker, image, noise, marble or wood)"
+        );
+    }
+
+    #[test]
+    fn missing_references() {
+        assert_eq!(
+            object_error("sphere center=0,0,0 radius=1 material=red"),
+            "unknown material 'red'"
+        );
+        assert_eq!(
+            object_error("sphere center=0,0,0 radius=1"),
+            "missing field 'material' for sphere"
+        );
+        assert_eq!(
+            parse_error("material m lambertian texture=checks").1,
+            "unknown texture 'checks'"
+        );
+        assert_eq!(
+            parse_error("material m").1,
+            "missing material type for material"
+        );
+        assert_eq!(
+            parse_error("material").1,
+            "missing material name for material"
+        );
+        assert!(parse_error("texture t image file=does/not/exist.ppm")
+            .1
+            .starts_with("could not load image"));
+        assert!(object_error("mesh file=does/not/exist.obj material=white")
+            .starts_with("could not load mesh"));
+    }
+
+    #[test]
+    fn duplicates() {
+        assert_eq!(
+            parse_error("camera\ncamera").1,
+            "camera is defined more than once"
+        );
+        assert_eq!(
+            parse_error("background solid color=0,0,0\nbackground solid color=0,0,0"),
+            (2, "background is defined more than once".to_string())
+        );
+        assert_eq!(
+            parse_error(&format!("{}material white metal albedo=1,1,1", MATERIALS)),
+            (3, "material 'white' is already defined".to_string())
+        );
+        assert_eq!(
+            parse_error("texture t solid color=0,0,0\ntexture t solid color=1,1,1").1,
+            "texture 't' is already defined"
+        );
+        assert_eq!(
+            object_error("sphere center=0,0,0 center=1,1,1 radius=1 material=white"),
+            "field 'center' is given more than once"
+        );
+    }
+
+    #[test]
+    fn invalid_syntax() {
+        assert_eq!(
+            object_error("sphere center=0,0,0 big radius=1 material=white"),
+            "expected 'key=value', found 'big'"
+        );
+        assert_eq!(
+            object_error("sphere center= radius=1 material=white"),
+            "invalid field 'center='"
+        );
+        assert_eq!(
+            object_error("sphere small center=0,0,0 radius=1 material=white"),
+            "unexpected 'small'"
+        );
+        assert_eq!(
+            parse_error("material m lambertian albedo=1,1,1 texture=t").1,
+            "fields 'albedo' and 'texture' can not be used together"
+        );
+    }
+
+    #[test]
+    fn invalid_values() {
+        assert_eq!(
+            object_error("sphere center=0,0,0 radius=big material=white"),
+            "invalid number for 'radius': 'big'"
+        );
+        assert_eq!(
+            object_error("sphere center=0,0,0 radius=inf material=white"),
+            "invalid number for 'radius': 'inf'"
+        );
+        assert_eq!(
+            object_error("sphere center=0,0 radius=1 material=white"),
+            "expected 3 comma separated values for 'center', found 2"
+        );
+        assert_eq!(
+            object_error("rect plane=xy x=1,0 y=0,1 k=0 material=white"),
+            "invalid range for 'x': 1 is greater than 0"
+        );
+        assert_eq!(
+            object_error("rect plane=xz x=0,1 z=2,2 k=0 material=white"),
+            "invalid range for 'z': 2 to 2 is empty"
+        );
+        assert_eq!(
+            object_error("rect plane=xw x=0,1 y=0,1 k=0 material=white"),
+            "invalid value for 'plane': 'xw' (expected xy, xz or yz)"
+        );
+        assert_eq!(
+            object_error("sphere center=0,0,0 radius=1 material=white density=0"),
+            "invalid value for 'density': 0 (must be positive)"
+        );
+        assert_eq!(
+            object_error("sphere center=0,0,0 radius=1 material=white scale=1,0,1"),
+            "invalid value for 'scale': factors must not be zero"
+        );
+        assert_eq!(
+            parse_error("background solid color=#ff00").1,
+            "invalid color for 'color': '#ff00'"
+        );
+        assert_eq!(
+            parse_error("texture t noise seed=-1").1,
+            "invalid value for 'seed': '-1'"
+        );
+        assert_eq!(
+            parse_error("camera pos=0,0,0 lookat=0,0,0").1,
+            "camera 'pos' and 'lookat' must differ"
+        );
+        assert_eq!(
+            parse_error("camera vfov=180").1,
+            "invalid value for 'vfov': 180 (must be in (0; 180))"
+        );
+        assert_eq!(
+            parse_error("camera aperture=-1").1,
+            "invalid value for 'aperture': -1 (must not be negative)"
+        );
+    }
+}
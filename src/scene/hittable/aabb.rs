@@ -20,8 +20,8 @@ impl Aabb {
     pub fn hit(&self, ray: &Ray, mut t_min: f64, mut t_max: f64) -> bool {
         for a in 0..3 {
             let inv_d = 1.0 / ray.dir[a];
-            let mut t0 = self.min[a] - (ray.origin[a] * inv_d);
-            let mut t1 = self.max[a] - (ray.origin[a] * inv_d);
+            let mut t0 = (self.min[a] - ray.origin[a]) * inv_d;
+            let mut t1 = (self.max[a] - ray.origin[a]) * inv_d;
 
             if inv_d < 0.0 {
                 std::mem::swap(&mut t0, &mut t1);
@@ -54,3 +54,173 @@ impl Aabb {
         Aabb::new(small, big)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::Aabb;
+    use crate::lalg::{Point3, Vec3};
+    use crate::ray::Ray;
+    use crate::scene::hittable::{
+        test_rng, AaBox, AaRect, AaRectPlaneCoords, Hittable, MovingSphere, Sphere, Triangle,
+    };
+    use crate::scene::material::DefaultMaterial;
+
+    fn unit_box() -> Aabb {
+        Aabb::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn hit_through_box() {
+        let ray = Ray::new(Point3::new(0.5, 0.5, -1.0), Vec3::new(0.0, 0.0, 1.0));
+
+        assert!(unit_box().hit(&ray, 0.001, f64::INFINITY));
+    }
+
+    #[test]
+    fn hit_from_inside() {
+        let ray = Ray::new(Point3::new(0.5, 0.5, 0.5), Vec3::new(-1.0, 2.0, 0.5));
+
+        assert!(unit_box().hit(&ray, 0.001, f64::INFINITY));
+    }
+
+    #[test]
+    fn miss_beside_box() {
+        let ray = Ray::new(Point3::new(1.5, 0.5, -1.0), Vec3::new(0.0, 0.0, 1.0));
+
+        assert!(!unit_box().hit(&ray, 0.001, f64::INFINITY));
+    }
+
+    #[test]
+    fn miss_behind_origin() {
+        let ray = Ray::new(Point3::new(0.5, 0.5, 2.0), Vec3::new(0.0, 0.0, 1.0));
+
+        assert!(!unit_box().hit(&ray, 0.001, f64::INFINITY));
+    }
+
+    #[test]
+    fn miss_out_of_range() {
+        let ray = Ray::new(Point3::new(0.5, 0.5, -1.0), Vec3::new(0.0, 0.0, 1.0));
+
+        assert!(!unit_box().hit(&ray, 0.001, 0.5));
+        assert!(!unit_box().hit(&ray, 2.5, f64::INFINITY));
+    }
+
+    #[test]
+    fn surrounding_box_contains_both() {
+        let other = Aabb::new(Point3::new(-1.0, 0.5, 0.5), Point3::new(0.5, 2.0, 0.5));
+        let aabb = Aabb::surrounding_box(unit_box(), other);
+
+        assert_eq!(aabb.min, Point3::new(-1.0, 0.0, 0.0));
+        assert_eq!(aabb.max, Point3::new(1.0, 2.0, 1.0));
+    }
+
+    fn coord() -> impl Strategy<Value = f64> {
+        -2.0..2.0
+    }
+
+    fn point3() -> impl Strategy<Value = Point3> {
+        (coord(), coord(), coord()).prop_map(|(x, y, z)| Point3::new(x, y, z))
+    }
+
+    /// Direction that is often parallel to an axis or a plane
+    fn direction() -> impl Strategy<Value = Vec3> {
+        let component = || prop_oneof![1 => Just(0.0), 3 => -1.0..1.0];
+
+        (component(), component(), component())
+            .prop_map(|(x, y, z)| Vec3::new(x, y, z))
+            .prop_filter("zero direction", |dir| dir.len_sq() > 1e-6)
+    }
+
+    /// Ray from anywhere around the primitives, sometimes starting on one of their planes
+    fn ray() -> impl Strategy<Value = Ray> {
+        let origin = (-5.0..5.0, -5.0..5.0, -5.0..5.0).prop_map(|(x, y, z)| Point3::new(x, y, z));
+        let on_plane = (point3(), 0..3usize, coord()).prop_map(|(mut p, axis, k)| {
+            match axis {
+                0 => p.x = k.round(),
+                1 => p.y = k.round(),
+                _ => p.z = k.round(),
+            }
+            p
+        });
+
+        (
+            prop_oneof![3 => origin, 1 => on_plane],
+            direction(),
+            0.0..1.0,
+        )
+            .prop_map(|(origin, dir, time)| Ray::with_time(origin, dir, time))
+    }
+
+    fn range() -> impl Strategy<Value = (f64, f64)> {
+        coord()
+            .prop_flat_map(|a| (Just(a), coord()))
+            .prop_map(|(a, b)| (a.min(b), a.max(b)))
+    }
+
+    fn primitive() -> impl Strategy<Value = Box<dyn Hittable>> {
+        let radius = prop_oneof![0.1..2.0, -2.0..-0.1];
+
+        let sphere = (point3(), radius.clone()).prop_map(|(center, radius)| {
+            Box::new(Sphere::new(center, radius, DefaultMaterial.boxed())) as Box<dyn Hittable>
+        });
+
+        let moving_sphere = (point3(), point3(), radius).prop_map(|(center0, center1, radius)| {
+            Box::new(MovingSphere::new(
+                (center0, center1),
+                (0.0, 1.0),
+                radius,
+                DefaultMaterial.boxed(),
+            )) as Box<dyn Hittable>
+        });
+
+        // integer coordinates, so rays start on the plane of a rectangle every now and then
+        let rect = (0..3usize, range(), range(), -2..=2).prop_map(|(plane, a, b, k)| {
+            let plane_coords = match plane {
+                0 => AaRectPlaneCoords::Xy { x: a, y: b },
+                1 => AaRectPlaneCoords::Xz { x: a, z: b },
+                _ => AaRectPlaneCoords::Yz { y: a, z: b },
+            };
+
+            Box::new(AaRect::new(plane_coords, k as f64, DefaultMaterial.boxed()))
+                as Box<dyn Hittable>
+        });
+
+        let triangle = (point3(), point3(), point3()).prop_map(|(a, b, c)| {
+            Box::new(Triangle::new(a, b, c, DefaultMaterial.boxed())) as Box<dyn Hittable>
+        });
+
+        let aa_box = (point3(), point3()).prop_map(|(p0, p1)| {
+            Box::new(AaBox::new(p0, p1, DefaultMaterial.boxed())) as Box<dyn Hittable>
+        });
+
+        prop_oneof![sphere, moving_sphere, rect, triangle, aa_box]
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4096))]
+
+        #[test]
+        fn bounding_box_agrees_with_hit(primitive in primitive(), ray in ray()) {
+            let aabb = primitive.bounding_box().unwrap();
+
+            if let Some(hit) = primitive.hit(&ray, 0.001, f64::INFINITY, &mut test_rng()) {
+                prop_assert!(hit.t.is_finite() && hit.t >= 0.001, "hit at t = {}", hit.t);
+                prop_assert!(
+                    aabb.hit(&ray, 0.001, f64::INFINITY),
+                    "{:?} hits {:?} at {:?} but not its {:?}",
+                    ray,
+                    primitive,
+                    hit.p,
+                    aabb
+                );
+
+                let eps = 1e-6;
+                for a in 0..3 {
+                    prop_assert!(aabb.min[a] - eps <= hit.p[a] && hit.p[a] <= aabb.max[a] + eps);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Axis-aligned boxes
+
+use rand::RngCore;
+
+use crate::lalg::{Point3, Vec3};
+use crate::ray::Ray;
+use crate::scene::material::Material;
+
+use super::aabb::Aabb;
+use super::{AaRect, AaRectPlaneCoords, HitRecord, Hittable, HittableList};
+
+/// A box object composed of six [axis-aligned rectangles][AaRect]
+#[derive(Debug)]
+pub struct AaBox {
+    min: Point3,
+    max: Point3,
+    sides: HittableList,
+}
+
+impl AaBox {
+    /// Default shorthand constructor (the corners may be given in any order)
+    pub fn new(p0: Point3, p1: Point3, mat_ptr: Box<dyn Material>) -> Self {
+        let min = Point3::new(p0.x.min(p1.x), p0.y.min(p1.y), p0.z.min(p1.z));
+        let max = Point3::new(p0.x.max(p1.x), p0.y.max(p1.y), p0.z.max(p1.z));
+
+        let x = (min.x, max.x);
+        let y = (min.y, max.y);
+        let z = (min.z, max.z);
+
+        let mut sides = HittableList::empty();
+
+        for k in [min.z, max.z] {
+            let rect = AaRect::new(AaRectPlaneCoords::Xy { x, y }, k, mat_ptr.clone());
+            sides.add(Box::new(rect));
+        }
+
+        for k in [min.y, max.y] {
+            let rect = AaRect::new(AaRectPlaneCoords::Xz { x, z }, k, mat_ptr.clone());
+            sides.add(Box::new(rect));
+        }
+
+        for k in [min.x, max.x] {
+            let rect = AaRect::new(AaRectPlaneCoords::Yz { y, z }, k, mat_ptr.clone());
+            sides.add(Box::new(rect));
+        }
+
+        AaBox { min, max, sides }
+    }
+
+    /// Return the corner with the smallest coordinates
+    pub fn min(&self) -> Point3 {
+        self.min
+    }
+
+    /// Return the corner with the largest coordinates
+    pub fn max(&self) -> Point3 {
+        self.max
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl Hittable for AaBox {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rng: &mut dyn RngCore) -> Option<HitRecord> {
+        self.sides.hit(ray, t_min, t_max, rng)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // padded, so flat boxes do not get an empty bounding box
+        const PADDING: f64 = 0.0001;
+
+        let padding = Vec3::new(PADDING, PADDING, PADDING);
+        Some(Aabb::new(self.min - padding, self.max + padding))
+    }
+}
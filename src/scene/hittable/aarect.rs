@@ -1,5 +1,7 @@
 //! Axis-aligned rectangles
 
+use rand::RngCore;
+
 use crate::lalg::{Point3, Vec3};
 use crate::ray::Ray;
 use crate::scene::material::Material;
@@ -36,14 +38,15 @@ impl AaRect {
 }
 
 impl Hittable for AaRect {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _rng: &mut dyn RngCore) -> Option<HitRecord> {
         let t = match self.plane_coords {
             Xy { .. } => (self.k - ray.origin.z) / ray.dir.z,
             Xz { .. } => (self.k - ray.origin.y) / ray.dir.y,
             Yz { .. } => (self.k - ray.origin.x) / ray.dir.x,
         };
 
-        if t < t_min || t > t_max {
+        // a ray in the plane of the rectangle gives NaN and never hits it
+        if !(t_min..=t_max).contains(&t) {
             return None;
         }
 
@@ -78,9 +81,20 @@ impl Hittable for AaRect {
             Yz { .. } => Vec3::new(1.0, 0.0, 0.0),
         };
 
+        // the sides of flat boxes have no extent in one direction
+        let coord = |x: f64, (min, max): (f64, f64)| {
+            if max > min {
+                (x - min) / (max - min)
+            } else {
+                0.0
+            }
+        };
+        let uv = (coord(f, first), coord(s, second));
+
         let p = ray.at(t);
 
-        let hit = HitRecord::new_with_face_normal(p, self.mat_ptr.clone(), t, ray, outward_normal);
+        let hit =
+            HitRecord::new_with_face_normal(p, self.mat_ptr.clone(), t, uv, ray, outward_normal);
 
         Some(hit)
     }
@@ -98,3 +112,101 @@ impl Hittable for AaRect {
         Some(Aabb::new(min, max))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{AaRect, AaRectPlaneCoords};
+    use crate::lalg::{Point3, Vec3};
+    use crate::ray::Ray;
+    use crate::scene::hittable::{test_rng, Hittable};
+    use crate::scene::material::DefaultMaterial;
+
+    const EPS: f64 = 1e-9;
+
+    /// Unit square in the plane `z = -1`
+    fn xy_rect() -> AaRect {
+        AaRect::new(
+            AaRectPlaneCoords::Xy {
+                x: (0.0, 1.0),
+                y: (0.0, 1.0),
+            },
+            -1.0,
+            DefaultMaterial.boxed(),
+        )
+    }
+
+    #[test]
+    fn hit_inside_bounds() {
+        let ray = Ray::new(Point3::new(0.25, 0.75, 0.0), Vec3::new(0.0, 0.0, -2.0));
+        let hit = xy_rect()
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .unwrap();
+
+        assert!((hit.t - 0.5).abs() < EPS);
+        assert!((hit.p - Point3::new(0.25, 0.75, -1.0)).len() < EPS);
+        assert!((hit.u - 0.25).abs() < EPS && (hit.v - 0.75).abs() < EPS);
+        assert_eq!(hit.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(hit.is_front_face);
+    }
+
+    #[test]
+    fn hit_from_behind() {
+        let ray = Ray::new(Point3::new(0.5, 0.5, -2.0), Vec3::new(0.0, 0.0, 1.0));
+        let hit = xy_rect()
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .unwrap();
+
+        assert_eq!(hit.normal, Vec3::new(0.0, 0.0, -1.0));
+        assert!(!hit.is_front_face);
+    }
+
+    #[test]
+    fn miss_outside_bounds() {
+        let ray = Ray::new(Point3::new(1.5, 0.5, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(xy_rect()
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .is_none());
+    }
+
+    #[test]
+    fn miss_out_of_range() {
+        let ray = Ray::new(Point3::new(0.5, 0.5, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(xy_rect().hit(&ray, 0.001, 0.5, &mut test_rng()).is_none());
+        assert!(xy_rect()
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .is_some());
+    }
+
+    #[test]
+    fn miss_parallel() {
+        let above = Ray::new(Point3::new(-1.0, 0.5, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let in_plane = Ray::new(Point3::new(-1.0, 0.5, -1.0), Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(xy_rect()
+            .hit(&above, 0.001, f64::INFINITY, &mut test_rng())
+            .is_none());
+        assert!(xy_rect()
+            .hit(&in_plane, 0.001, f64::INFINITY, &mut test_rng())
+            .is_none());
+    }
+
+    #[test]
+    fn zero_width_has_finite_uv() {
+        let line = AaRect::new(
+            AaRectPlaneCoords::Xy {
+                x: (0.5, 0.5),
+                y: (0.0, 1.0),
+            },
+            -1.0,
+            DefaultMaterial.boxed(),
+        );
+        let ray = Ray::new(Point3::new(0.5, 0.25, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = line
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .unwrap();
+
+        assert_eq!((hit.u, hit.v), (0.0, 0.25));
+    }
+}
@@ -0,0 +1,322 @@
+//! Bounding volume hierarchy
+
+use std::cmp::Ordering;
+
+use rand::RngCore;
+
+use crate::lalg::Point3;
+use crate::ray::Ray;
+
+use super::aabb::Aabb;
+use super::{HitRecord, Hittable, HittableList};
+
+/// Bounding volume hierarchy that accelerates hit tests on many objects
+///
+/// Objects are recursively partitioned by their [bounding boxes][Hittable::bounding_box].
+/// Objects without a bounding box (e.g. infinite planes) can not be part of the tree,
+/// so they are kept in an unbounded fallback list that is tested linearly.
+#[derive(Debug)]
+pub struct BvhNode {
+    root: Option<BvhTree>,
+    unbounded: HittableList,
+}
+
+#[derive(Debug)]
+enum BvhTree {
+    Leaf {
+        obj: Box<dyn Hittable>,
+        bbox: Aabb,
+    },
+    Branch {
+        left: Box<BvhTree>,
+        right: Box<BvhTree>,
+        bbox: Aabb,
+    },
+}
+
+impl BvhNode {
+    /// Build the hierarchy from a number of objects
+    pub fn new(objs: Vec<Box<dyn Hittable>>) -> Self {
+        let mut bounded = Vec::new();
+        let mut unbounded = HittableList::empty();
+
+        for obj in objs {
+            match obj.bounding_box() {
+                Some(bbox) => bounded.push((obj, bbox)),
+                None => unbounded.add(obj),
+            }
+        }
+
+        let root = if bounded.is_empty() {
+            None
+        } else {
+            Some(BvhTree::build(bounded))
+        };
+
+        BvhNode { root, unbounded }
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl From<HittableList> for BvhNode {
+    fn from(list: HittableList) -> Self {
+        BvhNode::new(list.0)
+    }
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rng: &mut dyn RngCore) -> Option<HitRecord> {
+        let mut hit = None;
+        let mut closest = t_max;
+
+        if let Some(root) = &self.root {
+            if let Some(rec) = root.hit(ray, t_min, closest, rng) {
+                closest = rec.t;
+                hit = Some(rec);
+            }
+        }
+
+        if let Some(rec) = self.unbounded.hit(ray, t_min, closest, rng) {
+            hit = Some(rec);
+        }
+
+        hit
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        if !self.unbounded.0.is_empty() {
+            return None;
+        }
+
+        self.root.as_ref().map(|root| root.bbox().clone())
+    }
+}
+
+impl BvhTree {
+    /// Recursively split the objects along the longest axis of their centroids
+    fn build(mut objs: Vec<(Box<dyn Hittable>, Aabb)>) -> Self {
+        if objs.len() == 1 {
+            let (obj, bbox) = objs.pop().unwrap();
+            return BvhTree::Leaf { obj, bbox };
+        }
+
+        let (min, max) = objs.iter().map(|(_, bbox)| centroid(bbox)).fold(
+            (
+                Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+                Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            ),
+            |(min, max), c| {
+                (
+                    Point3::new(min.x.min(c.x), min.y.min(c.y), min.z.min(c.z)),
+                    Point3::new(max.x.max(c.x), max.y.max(c.y), max.z.max(c.z)),
+                )
+            },
+        );
+
+        let extent = max - min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+
+        objs.sort_by(|(_, a), (_, b)| {
+            centroid(a)[axis]
+                .partial_cmp(&centroid(b)[axis])
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let right = objs.split_off(objs.len() / 2);
+        let left = BvhTree::build(objs);
+        let right = BvhTree::build(right);
+
+        let bbox = Aabb::surrounding_box(left.bbox().clone(), right.bbox().clone());
+
+        BvhTree::Branch {
+            left: Box::new(left),
+            right: Box::new(right),
+            bbox,
+        }
+    }
+
+    fn bbox(&self) -> &Aabb {
+        match self {
+            BvhTree::Leaf { bbox, .. } => bbox,
+            BvhTree::Branch { bbox, .. } => bbox,
+        }
+    }
+
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rng: &mut dyn RngCore) -> Option<HitRecord> {
+        if !self.bbox().hit(ray, t_min, t_max) {
+            return None;
+        }
+
+        match self {
+            BvhTree::Leaf { obj, .. } => obj.hit(ray, t_min, t_max, rng),
+            BvhTree::Branch { left, right, .. } => {
+                let hit_left = left.hit(ray, t_min, t_max, rng);
+                let closest = hit_left.as_ref().map_or(t_max, |rec| rec.t);
+
+                right.hit(ray, t_min, closest, rng).or(hit_left)
+            }
+        }
+    }
+}
+
+fn centroid(bbox: &Aabb) -> Point3 {
+    (bbox.min + bbox.max) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::BvhNode;
+    use crate::lalg::{Point3, Vec3};
+    use crate::ray::Ray;
+    use crate::scene::hittable::{
+        test_rng, AaBox, AaRect, AaRectPlaneCoords, Hittable, HittableList, Sphere,
+    };
+    use crate::scene::material::DefaultMaterial;
+
+    fn random_point(rng: &mut ChaCha8Rng, extent: f64) -> Point3 {
+        Point3::new(
+            rng.gen_range(-extent..extent),
+            rng.gen_range(-extent..extent),
+            rng.gen_range(-extent..extent),
+        )
+    }
+
+    fn random_range(rng: &mut ChaCha8Rng) -> (f64, f64) {
+        let a = rng.gen_range(-10.0..10.0);
+        (a, a + rng.gen_range(0.1..3.0))
+    }
+
+    /// Spheres (some of them hollow) and rectangles in every plane, the same for the same seed
+    fn random_objects(seed: u64, count: usize) -> Vec<Box<dyn Hittable>> {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+        (0..count)
+            .map(|i| -> Box<dyn Hittable> {
+                if i % 2 == 0 {
+                    let radius = rng.gen_range(0.1..2.0);
+                    let radius = if rng.gen_bool(0.3) { -radius } else { radius };
+
+                    Box::new(Sphere::new(
+                        random_point(&mut rng, 10.0),
+                        radius,
+                        DefaultMaterial.boxed(),
+                    ))
+                } else {
+                    let (a, b) = (random_range(&mut rng), random_range(&mut rng));
+                    let plane_coords = match rng.gen_range(0..3) {
+                        0 => AaRectPlaneCoords::Xy { x: a, y: b },
+                        1 => AaRectPlaneCoords::Xz { x: a, z: b },
+                        _ => AaRectPlaneCoords::Yz { y: a, z: b },
+                    };
+
+                    Box::new(AaRect::new(
+                        plane_coords,
+                        rng.gen_range(-10.0..10.0),
+                        DefaultMaterial.boxed(),
+                    ))
+                }
+            })
+            .collect()
+    }
+
+    /// Check that the hierarchy finds the same closest hit as a linear search for random rays
+    fn assert_same_hits(seed: u64, count: usize) {
+        let list = HittableList::new(random_objects(seed, count));
+        let bvh = BvhNode::new(random_objects(seed, count));
+
+        let mut rng = ChaCha8Rng::seed_from_u64(seed.wrapping_add(1));
+
+        for _ in 0..2000 {
+            let origin = random_point(&mut rng, 15.0);
+            let ray = Ray::new(origin, random_point(&mut rng, 10.0) - origin);
+
+            let expected = list.hit(&ray, 0.001, f64::INFINITY, &mut test_rng());
+            let actual = bvh.hit(&ray, 0.001, f64::INFINITY, &mut test_rng());
+
+            match (expected, actual) {
+                (None, None) => (),
+                (Some(expected), Some(actual)) => {
+                    assert!((expected.t - actual.t).abs() < 1e-9, "{:?}", ray);
+                    assert_eq!(expected.normal, actual.normal, "{:?}", ray);
+                }
+                (expected, actual) => panic!(
+                    "{:?}: linear search hits at {:?}, hierarchy at {:?}",
+                    ray,
+                    expected.map(|rec| rec.t),
+                    actual.map(|rec| rec.t)
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn flat_box() {
+        let objects = || -> Vec<Box<dyn Hittable>> {
+            vec![
+                Box::new(AaBox::new(
+                    Point3::new(-1.0, 0.0, -1.0),
+                    Point3::new(1.0, 0.0, 1.0),
+                    DefaultMaterial.boxed(),
+                )),
+                Box::new(Sphere::new(
+                    Point3::new(5.0, 5.0, 5.0),
+                    1.0,
+                    DefaultMaterial.boxed(),
+                )),
+            ]
+        };
+        let bvh = BvhNode::new(objects());
+        let list = HittableList::new(objects());
+        let ray = Ray::new(Point3::new(0.2, 3.0, 0.3), Vec3::new(0.0, -1.0, 0.0));
+
+        let expected = list
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .unwrap();
+        let actual = bvh
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .unwrap();
+        assert!((actual.t - 3.0).abs() < 1e-9);
+        assert_eq!(actual.t, expected.t);
+    }
+
+    #[test]
+    fn empty() {
+        let bvh = BvhNode::new(Vec::new());
+        let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(bvh
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .is_none());
+        assert!(bvh.bounding_box().is_none());
+    }
+
+    #[test]
+    fn single_object() {
+        assert_same_hits(0, 1);
+    }
+
+    #[test]
+    fn same_hits_as_linear_search() {
+        for seed in 1..20 {
+            assert_same_hits(seed, 2 * seed as usize + 1);
+        }
+    }
+
+    #[test]
+    fn same_hits_as_linear_search_with_many_objects() {
+        assert_same_hits(100, 200);
+    }
+}
@@ -0,0 +1,155 @@
+//! Participating media
+
+use rand::{Rng, RngCore};
+
+use crate::color::Color;
+use crate::lalg::Vec3;
+use crate::ray::Ray;
+use crate::scene::material::{Isotropic, Material};
+
+use super::aabb::Aabb;
+use super::{HitRecord, Hittable};
+
+/// Volume of constant density (like fog or smoke) inside a boundary object
+///
+/// Rays that enter the boundary are scattered at a random distance
+/// that depends on the density. The boundary must be convex.
+#[derive(Debug)]
+pub struct ConstantMedium {
+    boundary: Box<dyn Hittable>,
+    neg_inv_density: f64,
+    phase_function: Box<dyn Material>,
+}
+
+impl ConstantMedium {
+    /// Default shorthand constructor (for an isotropic medium of a single color)
+    pub fn new(boundary: Box<dyn Hittable>, density: f64, albedo: Color) -> Self {
+        ConstantMedium::with_phase_function(boundary, density, Isotropic::new(albedo).boxed())
+    }
+
+    /// Constructs a medium that scatters rays with an arbitrary material
+    pub fn with_phase_function(
+        boundary: Box<dyn Hittable>,
+        density: f64,
+        phase_function: Box<dyn Material>,
+    ) -> Self {
+        ConstantMedium {
+            boundary,
+            neg_inv_density: -1.0 / density,
+            phase_function,
+        }
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rng: &mut dyn RngCore) -> Option<HitRecord> {
+        // find where the ray enters and leaves the boundary
+        let enter = self
+            .boundary
+            .hit(ray, f64::NEG_INFINITY, f64::INFINITY, rng)?;
+        let exit = self
+            .boundary
+            .hit(ray, enter.t + 0.0001, f64::INFINITY, rng)?;
+
+        let t_enter = enter.t.max(t_min).max(0.0);
+        let t_exit = exit.t.min(t_max);
+
+        if t_enter >= t_exit {
+            return None;
+        }
+
+        let ray_len = ray.dir.len();
+        let dist_inside = (t_exit - t_enter) * ray_len;
+        // a random number in (0; 1], so the logarithm is finite
+        let hit_dist = self.neg_inv_density * (1.0 - rng.gen::<f64>()).ln();
+
+        if hit_dist > dist_inside {
+            return None;
+        }
+
+        let t = t_enter + (hit_dist / ray_len);
+
+        // normal and face are arbitrary inside a volume
+        let hit = HitRecord::new(
+            ray.at(t),
+            Vec3::new(1.0, 0.0, 0.0),
+            self.phase_function.clone(),
+            t,
+            (0.0, 0.0),
+            true,
+        );
+
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConstantMedium;
+    use crate::color::Color;
+    use crate::lalg::{Point3, Vec3};
+    use crate::ray::Ray;
+    use crate::scene::hittable::{test_rng, Hittable, Sphere};
+    use crate::scene::material::DefaultMaterial;
+
+    fn fog(density: f64) -> ConstantMedium {
+        let boundary = Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, DefaultMaterial.boxed());
+        ConstantMedium::new(Box::new(boundary), density, Color::white())
+    }
+
+    #[test]
+    fn transmittance_follows_density() {
+        let medium = fog(1.0);
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let mut rng = test_rng();
+
+        let count = 20_000;
+        let passed = (0..count)
+            .filter(|_| medium.hit(&ray, 0.001, f64::INFINITY, &mut rng).is_none())
+            .count();
+
+        // the ray travels a distance of 2 inside the medium
+        let expected = (-2.0_f64).exp();
+        assert!((passed as f64 / count as f64 - expected).abs() < 0.01);
+    }
+
+    #[test]
+    fn hits_inside_boundary() {
+        let medium = fog(10.0);
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -2.0));
+        let mut rng = test_rng();
+
+        for _ in 0..100 {
+            if let Some(rec) = medium.hit(&ray, 0.001, f64::INFINITY, &mut rng) {
+                assert!((2.0..=3.0).contains(&rec.t));
+                assert!(rec.p.len() <= 1.0 + 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_hits() {
+        let medium = fog(0.5);
+        let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.1, 0.0, -1.0));
+        let (mut a, mut b) = (test_rng(), test_rng());
+
+        for _ in 0..100 {
+            let t_a = medium
+                .hit(&ray, 0.001, f64::INFINITY, &mut a)
+                .map(|rec| rec.t);
+            let t_b = medium
+                .hit(&ray, 0.001, f64::INFINITY, &mut b)
+                .map(|rec| rec.t);
+            assert_eq!(t_a, t_b);
+        }
+    }
+}
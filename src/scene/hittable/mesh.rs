@@ -0,0 +1,423 @@
+//! Triangle meshes and Wavefront OBJ import
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use rand::RngCore;
+
+use crate::lalg::{Point3, Vec3};
+use crate::ray::Ray;
+use crate::scene::material::Material;
+
+use super::aabb::Aabb;
+use super::{BvhNode, HitRecord, Hittable, Triangle};
+
+/// Error that occurs while loading an OBJ file
+#[derive(Debug)]
+pub enum ObjError {
+    /// The file could not be read
+    Io(io::Error),
+    /// The file contents are invalid
+    Parse { line: usize, msg: String },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io(err) => write!(f, "Could not read OBJ file: {}", err),
+            ObjError::Parse { line, msg } => write!(f, "line {}: {}", line, msg),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ObjError::Io(err) => Some(err),
+            ObjError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for ObjError {
+    fn from(err: io::Error) -> Self {
+        ObjError::Io(err)
+    }
+}
+
+/// A mesh of triangles that share a material
+///
+/// The triangles are stored in a [bounding volume hierarchy][BvhNode],
+/// so even large models can be hit efficiently.
+#[derive(Debug)]
+pub struct TriangleMesh {
+    triangles: BvhNode,
+    len: usize,
+}
+
+impl TriangleMesh {
+    /// Default constructor
+    pub fn new(triangles: Vec<Triangle>) -> Self {
+        let len = triangles.len();
+        let triangles = triangles
+            .into_iter()
+            .map(|tri| tri.boxed() as Box<dyn Hittable>)
+            .collect();
+
+        TriangleMesh {
+            triangles: BvhNode::new(triangles),
+            len,
+        }
+    }
+
+    /// Load a mesh from a Wavefront OBJ file
+    ///
+    /// Only vertices (`v`), vertex normals (`vn`) and faces (`f`) are read.
+    /// Polygons with more than three vertices are split into triangles.
+    pub fn from_obj(path: impl AsRef<Path>, mat_ptr: Box<dyn Material>) -> Result<Self, ObjError> {
+        let src = fs::read_to_string(path)?;
+        TriangleMesh::parse_obj(&src, mat_ptr)
+    }
+
+    /// Parse a mesh from the contents of a Wavefront OBJ file
+    pub fn parse_obj(src: &str, mat_ptr: Box<dyn Material>) -> Result<Self, ObjError> {
+        let mut vertices: Vec<Point3> = Vec::new();
+        let mut normals: Vec<Vec3> = Vec::new();
+        let mut triangles = Vec::new();
+
+        for (idx, line) in src.lines().enumerate() {
+            let line_nr = idx + 1;
+            let error = |msg: String| ObjError::Parse { line: line_nr, msg };
+
+            // comments run to the end of the line
+            let line = line.split('#').next().unwrap_or_default();
+
+            let mut words = line.split_whitespace();
+            let keyword = match words.next() {
+                Some(keyword) => keyword,
+                None => continue,
+            };
+
+            match keyword {
+                "v" | "vn" => {
+                    let coords = words
+                        .take(3)
+                        .map(|word| word.parse::<f64>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|err| error(format!("invalid coordinate: {}", err)))?;
+
+                    let v = match coords[..] {
+                        [x, y, z] => Vec3::new(x, y, z),
+                        _ => {
+                            return Err(error(format!("expected 3 coordinates for '{}'", keyword)))
+                        }
+                    };
+
+                    if keyword == "v" {
+                        vertices.push(v);
+                    } else {
+                        normals.push(v);
+                    }
+                }
+                "f" => {
+                    let corners = words
+                        .map(|word| parse_corner(word, vertices.len(), normals.len()))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(error)?;
+
+                    if corners.len() < 3 {
+                        return Err(error(String::from("face needs at least 3 vertices")));
+                    }
+
+                    // triangulate as a fan around the first corner
+                    for i in 1..corners.len() - 1 {
+                        let (a, b, c) = (corners[0], corners[i], corners[i + 1]);
+                        let points = [vertices[a.0], vertices[b.0], vertices[c.0]];
+
+                        let tri = match (a.1, b.1, c.1) {
+                            (Some(na), Some(nb), Some(nc)) => Triangle::with_normals(
+                                points,
+                                [normals[na], normals[nb], normals[nc]],
+                                mat_ptr.clone(),
+                            ),
+                            _ => Triangle::new(points[0], points[1], points[2], mat_ptr.clone()),
+                        };
+
+                        triangles.push(tri);
+                    }
+                }
+                // texture coordinates, groups, materials etc. are not supported
+                _ => (),
+            }
+        }
+
+        if triangles.is_empty() {
+            return Err(ObjError::Parse {
+                line: src.lines().count(),
+                msg: String::from("OBJ file contains no faces"),
+            });
+        }
+
+        Ok(TriangleMesh::new(triangles))
+    }
+
+    /// Return the number of triangles
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the mesh has no triangles
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rng: &mut dyn RngCore) -> Option<HitRecord> {
+        self.triangles.hit(ray, t_min, t_max, rng)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.triangles.bounding_box()
+    }
+}
+
+/// Parse a face corner like `1`, `1/2`, `1//3` or `1/2/3` into vertex and normal indices
+fn parse_corner(
+    word: &str,
+    vertex_count: usize,
+    normal_count: usize,
+) -> Result<(usize, Option<usize>), String> {
+    let mut parts = word.split('/');
+
+    let vertex = parts.next().unwrap_or_default();
+    let vertex = resolve_index(vertex, vertex_count)
+        .ok_or_else(|| format!("invalid vertex index in '{}'", word))?;
+
+    let normal = match parts.nth(1) {
+        Some(normal) if !normal.is_empty() => Some(
+            resolve_index(normal, normal_count)
+                .ok_or_else(|| format!("invalid normal index in '{}'", word))?,
+        ),
+        _ => None,
+    };
+
+    Ok((vertex, normal))
+}
+
+/// Convert a one-based (or negative, relative) OBJ index to a zero-based index
+fn resolve_index(s: &str, count: usize) -> Option<usize> {
+    let idx: isize = s.parse().ok()?;
+
+    let idx = if idx > 0 {
+        idx as usize - 1
+    } else {
+        count.checked_sub(idx.unsigned_abs())?
+    };
+
+    (idx < count).then_some(idx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ObjError, TriangleMesh};
+    use crate::lalg::{Point3, Vec3};
+    use crate::ray::Ray;
+    use crate::scene::hittable::{test_rng, HitRecord, Hittable};
+    use crate::scene::material::DefaultMaterial;
+
+    /// Unit square in the plane `z = -1` as vertices 1 to 4, with normals tilted to +x and -x
+    const SQUARE: &str = "
+v 0 0 -1
+v 1 0 -1
+v 1 1 -1
+v 0 1 -1
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 1 0 1
+vn -1 0 1
+";
+
+    fn parse(src: &str) -> TriangleMesh {
+        TriangleMesh::parse_obj(src, DefaultMaterial.boxed())
+            .unwrap_or_else(|err| panic!("could not parse OBJ: {}", err))
+    }
+
+    /// Return the line and message of the error of an invalid OBJ file
+    fn parse_error(src: &str) -> (usize, String) {
+        match TriangleMesh::parse_obj(src, DefaultMaterial.boxed()) {
+            Ok(_) => panic!("OBJ is valid:\n{}", src),
+            Err(ObjError::Parse { line, msg }) => (line, msg),
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+    }
+
+    /// Shoot a ray from `(x, y, 0)` towards negative z
+    fn hit(mesh: &TriangleMesh, x: f64, y: f64) -> Option<HitRecord> {
+        let ray = Ray::new(Point3::new(x, y, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        mesh.hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+    }
+
+    #[test]
+    fn single_triangle() {
+        let mesh = parse(&format!("{}f 1 2 3", SQUARE));
+
+        assert_eq!(mesh.len(), 1);
+        let rec = hit(&mesh, 0.75, 0.25).unwrap();
+        assert!((rec.t - 1.0).abs() < 1e-9);
+        assert_eq!(rec.normal, Vec3::new(0.0, 0.0, 1.0));
+
+        // the other half of the square
+        assert!(hit(&mesh, 0.25, 0.75).is_none());
+    }
+
+    #[test]
+    fn fan_triangulation() {
+        let mesh = parse(&format!("{}f 1 2 3 4", SQUARE));
+        assert_eq!(mesh.len(), 2);
+        assert!(hit(&mesh, 0.75, 0.25).is_some());
+        assert!(hit(&mesh, 0.25, 0.75).is_some());
+
+        let pentagon = parse("v 0 0 0\nv 1 0 0\nv 2 1 0\nv 1 2 0\nv 0 1 0\nf 1 2 3 4 5");
+        assert_eq!(pentagon.len(), 3);
+    }
+
+    #[test]
+    fn face_forms() {
+        for face in [
+            "f 1 2 3",
+            "f 1/1 2/2 3/3",
+            "f 1//1 2//1 3//1",
+            "f 1/1/1 2/2/1 3/3/1",
+        ] {
+            let mesh = parse(&format!("{}{}", SQUARE, face));
+            assert!(hit(&mesh, 0.75, 0.25).is_some(), "{}", face);
+        }
+    }
+
+    #[test]
+    fn vertex_normals_are_interpolated() {
+        // the normal is tilted to +x at vertex 1 and to -x at vertices 2 and 3
+        let mesh = parse(&format!("{}f 1//1 2//2 3//2", SQUARE));
+
+        let near_first = hit(&mesh, 0.05, 0.01).unwrap().normal;
+        let near_second = hit(&mesh, 0.95, 0.01).unwrap().normal;
+
+        assert!(near_first.x > 0.5 && near_second.x < -0.5);
+        assert!((near_first.len() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn zero_normals_fall_back_to_face_normal() {
+        let mesh = parse(&format!("{}vn 0 0 0\nf 1//3 2//3 3//3", SQUARE));
+        let normal = hit(&mesh, 0.75, 0.25).unwrap().normal;
+        assert!(
+            (normal - Vec3::new(0.0, 0.0, 1.0)).len() < 1e-9,
+            "{:?}",
+            normal
+        );
+
+        // only the zero normal is replaced
+        let mesh = parse(&format!("{}vn 0 0 0\nf 1//3 2//1 3//1", SQUARE));
+        let normal = hit(&mesh, 0.75, 0.25).unwrap().normal;
+        assert!(normal.x > 0.0 && (normal.len() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn missing_normals_give_flat_shading() {
+        let mesh = parse(&format!("{}f 1/1/1 2/2 3/3/2", SQUARE));
+        assert_eq!(
+            hit(&mesh, 0.75, 0.25).unwrap().normal,
+            Vec3::new(0.0, 0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn negative_indices() {
+        let mesh = parse(&format!("{}f -4 -3 -2 -1", SQUARE));
+        assert_eq!(mesh.len(), 2);
+
+        // relative to the vertices defined so far
+        let mesh = parse("v 9 9 9\nv 0 0 -1\nv 1 0 -1\nv 1 1 -1\nf -3 -2 -1\nv 9 9 9");
+        assert!(hit(&mesh, 0.75, 0.25).is_some());
+
+        let mesh = parse(&format!("{}f 1//-2 2//-1 3//-1", SQUARE));
+        assert!(hit(&mesh, 0.05, 0.01).unwrap().normal.x > 0.5);
+    }
+
+    #[test]
+    fn ignores_comments_and_unsupported_statements() {
+        let mesh = parse(&format!(
+            "# a comment\n{}\no square\ng group\nusemtl red\ns off\nf 1 2 3 # trailing words\n",
+            SQUARE
+        ));
+        assert_eq!(mesh.len(), 1);
+    }
+
+    #[test]
+    fn invalid_indices() {
+        assert_eq!(
+            parse_error(&format!("{}f 1 2 5", SQUARE)),
+            (12, String::from("invalid vertex index in '5'"))
+        );
+        assert_eq!(
+            parse_error(&format!("{}f 0 1 2", SQUARE)).1,
+            "invalid vertex index in '0'"
+        );
+        assert_eq!(
+            parse_error(&format!("{}f -5 1 2", SQUARE)).1,
+            "invalid vertex index in '-5'"
+        );
+        assert_eq!(
+            parse_error(&format!("{}f 1//3 2//1 3//1", SQUARE)).1,
+            "invalid normal index in '1//3'"
+        );
+        assert_eq!(
+            parse_error("v 0 0 0\nv 1 0 0\nf 1 2 3\nv 0 1 0").1,
+            "invalid vertex index in '3'"
+        );
+    }
+
+    #[test]
+    fn malformed_lines() {
+        assert_eq!(
+            parse_error("v 1 2\nf 1 1 1"),
+            (1, String::from("expected 3 coordinates for 'v'"))
+        );
+        assert!(parse_error("v 1 x 3\nf 1 1 1")
+            .1
+            .starts_with("invalid coordinate"));
+        assert_eq!(
+            parse_error("vn 1 0\nv 0 0 0\nf 1 1 1").1,
+            "expected 3 coordinates for 'vn'"
+        );
+        assert_eq!(
+            parse_error(&format!("{}f 1 2", SQUARE)).1,
+            "face needs at least 3 vertices"
+        );
+        assert_eq!(
+            parse_error(&format!("{}f 1 a 2", SQUARE)).1,
+            "invalid vertex index in 'a'"
+        );
+        assert_eq!(
+            parse_error(SQUARE),
+            (11, String::from("OBJ file contains no faces"))
+        );
+    }
+
+    #[test]
+    fn missing_file() {
+        let err =
+            TriangleMesh::from_obj("does/not/exist.obj", DefaultMaterial.boxed()).unwrap_err();
+        assert!(matches!(err, ObjError::Io(_)));
+    }
+}
@@ -2,22 +2,48 @@
 
 use std::fmt::Debug;
 
+use rand::RngCore;
+#[cfg(test)]
+use rand::SeedableRng;
+#[cfg(test)]
+use rand_chacha::ChaCha8Rng;
+
 use crate::lalg::{Point3, Vec3};
 use crate::ray::Ray;
 use crate::scene::material::Material;
 use aabb::Aabb;
 
 mod aabb;
+mod aabox;
 mod aarect;
+mod bvh;
+mod medium;
+mod mesh;
+mod moving_sphere;
 mod sphere;
+mod transform;
+mod triangle;
 
+pub use aabox::AaBox;
 pub use aarect::{AaRect, AaRectPlaneCoords};
+pub use bvh::BvhNode;
+pub use medium::ConstantMedium;
+pub use mesh::{ObjError, TriangleMesh};
+pub use moving_sphere::MovingSphere;
 pub use sphere::Sphere;
+pub use transform::Transform;
+pub use triangle::Triangle;
+
+/// Random number generator with a fixed seed for the tests of objects
+#[cfg(test)]
+pub(crate) fn test_rng() -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(0)
+}
 
 /// Trait for objects that can be hit
 pub trait Hittable: Debug + Send + Sync {
     /// Function that indicates whether a ray hits the object
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rng: &mut dyn RngCore) -> Option<HitRecord>;
 
     /// Return bounding box
     fn bounding_box(&self) -> Option<Aabb>;
@@ -30,6 +56,10 @@ pub struct HitRecord {
     pub normal: Vec3,
     pub mat_ptr: Box<dyn Material>,
     pub t: f64,
+    /// Horizontal surface coordinate in `[0; 1]`
+    pub u: f64,
+    /// Vertical surface coordinate in `[0; 1]`
+    pub v: f64,
     pub is_front_face: bool,
 }
 
@@ -40,6 +70,7 @@ impl HitRecord {
         normal: Vec3,
         mat_ptr: Box<dyn Material>,
         t: f64,
+        (u, v): (f64, f64),
         is_front_face: bool,
     ) -> Self {
         HitRecord {
@@ -47,6 +78,8 @@ impl HitRecord {
             normal,
             mat_ptr,
             t,
+            u,
+            v,
             is_front_face,
         }
     }
@@ -57,6 +90,7 @@ impl HitRecord {
         p: Point3,
         mat_ptr: Box<dyn Material>,
         t: f64,
+        uv: (f64, f64),
         ray: &Ray,
         outward_normal: Vec3,
     ) -> Self {
@@ -68,7 +102,7 @@ impl HitRecord {
             -outward_normal
         };
 
-        HitRecord::new(p, normal, mat_ptr, t, is_front_face)
+        HitRecord::new(p, normal, mat_ptr, t, uv, is_front_face)
     }
 }
 
@@ -105,12 +139,12 @@ impl Default for HittableList {
 }
 
 impl Hittable for HittableList {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rng: &mut dyn RngCore) -> Option<HitRecord> {
         let mut hit = None;
         let mut closest = t_max;
 
         for obj in self.0.iter() {
-            if let Some(rec) = obj.hit(ray, t_min, closest) {
+            if let Some(rec) = obj.hit(ray, t_min, closest, rng) {
                 closest = rec.t;
                 hit = Some(rec);
             }
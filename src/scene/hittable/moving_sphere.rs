@@ -0,0 +1,113 @@
+use rand::RngCore;
+
+use crate::lalg::Point3;
+use crate::ray::Ray;
+use crate::scene::material::Material;
+
+use super::aabb::Aabb;
+use super::sphere;
+use super::{HitRecord, Hittable};
+
+/// A sphere that moves linearly from `center0` at `time0` to `center1` at `time1`
+#[derive(Debug, Clone)]
+pub struct MovingSphere {
+    pub center0: Point3,
+    pub center1: Point3,
+    pub time0: f64,
+    pub time1: f64,
+    pub radius: f64,
+    pub mat_ptr: Box<dyn Material>,
+}
+
+impl MovingSphere {
+    /// Default shorthand constructor
+    pub fn new(
+        (center0, center1): (Point3, Point3),
+        (time0, time1): (f64, f64),
+        radius: f64,
+        mat_ptr: Box<dyn Material>,
+    ) -> Self {
+        MovingSphere {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            mat_ptr,
+        }
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+
+    /// Return the center at a point in time
+    ///
+    /// The sphere rests at its key positions before `time0` and after `time1`.
+    pub fn center(&self, time: f64) -> Point3 {
+        if self.time1 <= self.time0 {
+            return self.center0;
+        }
+
+        let progress = ((time - self.time0) / (self.time1 - self.time0)).clamp(0.0, 1.0);
+        self.center0 + progress * (self.center1 - self.center0)
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _rng: &mut dyn RngCore) -> Option<HitRecord> {
+        sphere::hit_sphere(
+            self.center(ray.time),
+            self.radius,
+            self.mat_ptr.as_ref(),
+            ray,
+            t_min,
+            t_max,
+        )
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // cover the whole motion between the two key positions
+        let box0 = sphere::sphere_box(self.center0, self.radius);
+        let box1 = sphere::sphere_box(self.center1, self.radius);
+
+        Some(Aabb::surrounding_box(box0, box1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MovingSphere;
+    use crate::lalg::{Point3, Vec3};
+    use crate::ray::Ray;
+    use crate::scene::hittable::{test_rng, Hittable, Sphere};
+    use crate::scene::material::DefaultMaterial;
+
+    #[test]
+    fn hits_like_a_sphere_at_the_center_of_the_time() {
+        let moving = MovingSphere::new(
+            (Point3::new(0.0, 0.0, -2.0), Point3::new(0.0, 1.0, -2.0)),
+            (0.0, 1.0),
+            0.5,
+            DefaultMaterial.boxed(),
+        );
+
+        for time in [-1.0, 0.0, 0.3, 0.5, 1.0, 2.0] {
+            let sphere = Sphere::new(moving.center(time), 0.5, DefaultMaterial.boxed());
+            let ray = Ray::with_time(Point3::origin(), Vec3::new(0.0, 0.2, -1.0), time);
+
+            let expected = sphere
+                .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+                .map(|rec| rec.t);
+            let actual = moving
+                .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+                .map(|rec| rec.t);
+            assert_eq!(expected, actual, "time {}", time);
+        }
+
+        let bbox = moving.bounding_box().unwrap();
+        assert_eq!(bbox.min, Point3::new(-0.5, -0.5, -2.5));
+        assert_eq!(bbox.max, Point3::new(0.5, 1.5, -1.5));
+    }
+}
@@ -1,3 +1,7 @@
+use std::f64::consts::PI;
+
+use rand::RngCore;
+
 use crate::lalg::{Point3, Vec3};
 use crate::ray::Ray;
 use crate::scene::material::Material;
@@ -27,45 +31,78 @@ impl Sphere {
     pub fn builder() -> SphereCenterBuilder {
         SphereCenterBuilder
     }
+
+    /// Return the surface coordinates of a point on the unit sphere
+    ///
+    /// `u` goes around the y axis starting at `-x`, `v` goes from the bottom to the top.
+    pub(super) fn uv(p: Point3) -> (f64, f64) {
+        let theta = (-p.y).clamp(-1.0, 1.0).acos();
+        let phi = (-p.z).atan2(p.x) + PI;
+
+        (phi / (2.0 * PI), theta / PI)
+    }
 }
 
 impl Hittable for Sphere {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let oc = ray.origin - self.center;
-        let a = ray.dir.len_sq();
-        let half_b = oc.dot(ray.dir);
-        let c = oc.len_sq() - self.radius.powi(2);
-
-        let discriminant = half_b.powi(2) - (a * c);
-        if discriminant < 0.0 {
-            return None;
-        }
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _rng: &mut dyn RngCore) -> Option<HitRecord> {
+        hit_sphere(
+            self.center,
+            self.radius,
+            self.mat_ptr.as_ref(),
+            ray,
+            t_min,
+            t_max,
+        )
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(sphere_box(self.center, self.radius))
+    }
+}
+
+/// Intersect a ray with a sphere (shared by [Sphere] and [MovingSphere][super::MovingSphere])
+pub(super) fn hit_sphere(
+    center: Point3,
+    radius: f64,
+    mat_ptr: &dyn Material,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<HitRecord> {
+    let oc = ray.origin - center;
+    let a = ray.dir.len_sq();
+    let half_b = oc.dot(ray.dir);
+    let c = oc.len_sq() - radius.powi(2);
+
+    let discriminant = half_b.powi(2) - (a * c);
+    if discriminant < 0.0 {
+        return None;
+    }
 
-        let mut root = (-half_b - discriminant.sqrt()) / a;
+    let mut root = (-half_b - discriminant.sqrt()) / a;
+    if root < t_min || t_max < root {
+        root = (-half_b + discriminant.sqrt()) / a;
         if root < t_min || t_max < root {
-            root = (-half_b + discriminant.sqrt()) / a;
-            if root < t_min || t_max < root {
-                return None;
-            }
+            return None;
         }
+    }
 
-        let p = ray.at(root);
-        let outward_normal = (p - self.center) / self.radius;
+    let p = ray.at(root);
+    let outward_normal = (p - center) / radius;
+    let uv = Sphere::uv(outward_normal);
 
-        let hit =
-            HitRecord::new_with_face_normal(p, self.mat_ptr.clone(), root, ray, outward_normal);
+    let hit =
+        HitRecord::new_with_face_normal(p, mat_ptr.box_clone(), root, uv, ray, outward_normal);
 
-        Some(hit)
-    }
+    Some(hit)
+}
 
-    fn bounding_box(&self) -> Option<Aabb> {
-        let aabb = Aabb {
-            min: self.center - Vec3::new(self.radius, self.radius, self.radius),
-            max: self.center + Vec3::new(self.radius, self.radius, self.radius),
-        };
+/// Return the bounding box of a sphere
+pub(super) fn sphere_box(center: Point3, radius: f64) -> Aabb {
+    // a negative radius (for hollow spheres) only flips the normals
+    let radius = Vec3::new(radius.abs(), radius.abs(), radius.abs());
 
-        Some(aabb)
-    }
+    Aabb::new(center - radius, center + radius)
 }
 
 pub struct SphereCenterBuilder;
@@ -129,3 +166,88 @@ impl SphereBuilder {
         Box::new(self.build())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Sphere;
+    use crate::lalg::{Point3, Vec3};
+    use crate::ray::Ray;
+    use crate::scene::hittable::{test_rng, Hittable};
+    use crate::scene::material::DefaultMaterial;
+
+    const EPS: f64 = 1e-9;
+
+    fn sphere(radius: f64) -> Sphere {
+        Sphere::new(Point3::new(0.0, 0.0, -2.0), radius, DefaultMaterial.boxed())
+    }
+
+    #[test]
+    fn hit_from_outside() {
+        let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = sphere(1.0)
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .unwrap();
+
+        assert!((hit.t - 1.0).abs() < EPS);
+        assert!((hit.p - Point3::new(0.0, 0.0, -1.0)).len() < EPS);
+        assert_eq!(hit.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(hit.is_front_face);
+    }
+
+    #[test]
+    fn miss() {
+        let ray = Ray::new(Point3::new(0.0, 1.5, 0.0), Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(sphere(1.0)
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .is_none());
+    }
+
+    #[test]
+    fn hit_from_inside() {
+        let ray = Ray::new(Point3::new(0.0, 0.0, -2.0), Vec3::new(1.0, 0.0, 0.0));
+        let hit = sphere(1.0)
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .unwrap();
+
+        assert!((hit.t - 1.0).abs() < EPS);
+        assert_eq!(hit.normal, Vec3::new(-1.0, 0.0, 0.0));
+        assert!(!hit.is_front_face);
+    }
+
+    #[test]
+    fn hit_respects_range() {
+        let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let sphere = sphere(1.0);
+
+        // the far side is hit if the near one is out of range
+        let hit = sphere
+            .hit(&ray, 1.5, f64::INFINITY, &mut test_rng())
+            .unwrap();
+        assert!((hit.t - 3.0).abs() < EPS);
+
+        assert!(sphere.hit(&ray, 0.001, 0.5, &mut test_rng()).is_none());
+        assert!(sphere
+            .hit(&ray, 3.5, f64::INFINITY, &mut test_rng())
+            .is_none());
+    }
+
+    #[test]
+    fn negative_radius_flips_normals() {
+        let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = sphere(-1.0)
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .unwrap();
+
+        assert!((hit.t - 1.0).abs() < EPS);
+        assert!(!hit.is_front_face);
+    }
+
+    #[test]
+    fn bounding_box_of_negative_radius() {
+        let aabb = sphere(-1.0).bounding_box().unwrap();
+
+        assert_eq!(aabb.min, Point3::new(-1.0, -1.0, -3.0));
+        assert_eq!(aabb.max, Point3::new(1.0, 1.0, -1.0));
+    }
+}
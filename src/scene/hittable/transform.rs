@@ -0,0 +1,266 @@
+//! Instancing transformations of hittables
+
+use rand::RngCore;
+
+use crate::lalg::{Mat4, Point3, Vec3};
+use crate::ray::Ray;
+
+use super::aabb::Aabb;
+use super::{HitRecord, Hittable};
+
+/// Wrapper that places an object with an affine transformation
+///
+/// Incoming rays are transformed into the object space of the wrapped object,
+/// and the resulting hit is transformed back into world space.
+/// Transformations are chained in the order they are applied to the object,
+/// e.g. `Transform::new(obj).scale(..).rotate_y(45.0).translate(..)` scales first and moves last.
+#[derive(Debug)]
+pub struct Transform {
+    obj: Box<dyn Hittable>,
+    matrix: Mat4,
+    inverse: Mat4,
+    /// Inverse transpose that transforms normals
+    normal_matrix: Mat4,
+}
+
+impl Transform {
+    /// Constructs a wrapper with the identity transformation
+    pub fn new(obj: Box<dyn Hittable>) -> Self {
+        Transform::from_matrices(obj, Mat4::identity(), Mat4::identity())
+    }
+
+    /// Constructs a wrapper with an arbitrary transformation matrix
+    ///
+    /// Return `None` if the matrix is not invertible.
+    pub fn with_matrix(obj: Box<dyn Hittable>, matrix: Mat4) -> Option<Self> {
+        let inverse = matrix.inverse()?;
+        Some(Transform::from_matrices(obj, matrix, inverse))
+    }
+
+    /// Move the object by `offset`
+    pub fn translate(self, offset: Vec3) -> Self {
+        self.then(Mat4::translation(offset), Mat4::translation(-offset))
+    }
+
+    /// Rotate the object around the x axis by `angle` degrees
+    pub fn rotate_x(self, angle: f64) -> Self {
+        self.then(Mat4::rotation_x(angle), Mat4::rotation_x(-angle))
+    }
+
+    /// Rotate the object around the y axis by `angle` degrees
+    pub fn rotate_y(self, angle: f64) -> Self {
+        self.then(Mat4::rotation_y(angle), Mat4::rotation_y(-angle))
+    }
+
+    /// Rotate the object around the z axis by `angle` degrees
+    pub fn rotate_z(self, angle: f64) -> Self {
+        self.then(Mat4::rotation_z(angle), Mat4::rotation_z(-angle))
+    }
+
+    /// Scale the object by the factors of each axis
+    ///
+    /// Panics if a factor is zero.
+    pub fn scale(self, factors: Vec3) -> Self {
+        assert!(
+            factors.x != 0.0 && factors.y != 0.0 && factors.z != 0.0,
+            "Scale factors must not be zero"
+        );
+
+        let inverse = Vec3::new(1.0 / factors.x, 1.0 / factors.y, 1.0 / factors.z);
+        self.then(Mat4::scaling(factors), Mat4::scaling(inverse))
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+
+    /// Apply another transformation after the current one
+    fn then(self, matrix: Mat4, inverse: Mat4) -> Self {
+        Transform::from_matrices(self.obj, matrix * self.matrix, self.inverse * inverse)
+    }
+
+    fn from_matrices(obj: Box<dyn Hittable>, matrix: Mat4, inverse: Mat4) -> Self {
+        Transform {
+            obj,
+            matrix,
+            inverse,
+            normal_matrix: inverse.transpose(),
+        }
+    }
+}
+
+impl Hittable for Transform {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rng: &mut dyn RngCore) -> Option<HitRecord> {
+        let local_ray = Ray::with_time(
+            self.inverse.transform_point(ray.origin),
+            self.inverse.transform_vec(ray.dir),
+            ray.time,
+        );
+
+        let mut rec = self.obj.hit(&local_ray, t_min, t_max, rng)?;
+
+        rec.p = self.matrix.transform_point(rec.p);
+        rec.normal = self.normal_matrix.transform_vec(rec.normal).unit_vec();
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let bbox = self.obj.bounding_box()?;
+
+        let mut min = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+        let mut max = Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+
+        for i in 0..8 {
+            let corner = Point3::new(
+                if i & 1 == 0 { bbox.min.x } else { bbox.max.x },
+                if i & 2 == 0 { bbox.min.y } else { bbox.max.y },
+                if i & 4 == 0 { bbox.min.z } else { bbox.max.z },
+            );
+            let p = self.matrix.transform_point(corner);
+
+            min = Point3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+            max = Point3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+        }
+
+        Some(Aabb::new(min, max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transform;
+    use crate::lalg::{Mat4, Point3, Vec3};
+    use crate::ray::Ray;
+    use crate::scene::hittable::{test_rng, Hittable, Sphere};
+    use crate::scene::material::DefaultMaterial;
+
+    const EPS: f64 = 1e-9;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).len() < EPS, "{:?} != {:?}", a, b);
+    }
+
+    fn unit_sphere() -> Box<Sphere> {
+        Box::new(Sphere::new(Point3::origin(), 1.0, DefaultMaterial.boxed()))
+    }
+
+    #[test]
+    fn translated_sphere() {
+        let transform = Transform::new(unit_sphere()).translate(Vec3::new(0.0, 0.0, -3.0));
+        let ray = Ray::new(Point3::origin(), Vec3::new(0.0, 0.0, -1.0));
+        let rec = transform
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .unwrap();
+
+        assert!((rec.t - 2.0).abs() < EPS);
+        assert_close(rec.p, Point3::new(0.0, 0.0, -2.0));
+        assert_close(rec.normal, Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn scaled_sphere_normals() {
+        // ellipsoid x^2/4 + y^2 + z^2 = 1
+        let transform = Transform::new(unit_sphere()).scale(Vec3::new(2.0, 1.0, 1.0));
+
+        let p = Point3::new(1.0, 0.0, 0.75_f64.sqrt());
+        let ray = Ray::new(Point3::new(1.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = transform
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .unwrap();
+
+        // the normal is the gradient of the implicit surface
+        let gradient = Vec3::new(p.x / 4.0, p.y, p.z).unit_vec();
+        assert_close(rec.p, p);
+        assert_close(rec.normal, gradient);
+    }
+
+    #[test]
+    fn transformations_apply_in_order() {
+        // scale, then rotate the x axis onto -z, then move
+        let transform = Transform::new(unit_sphere())
+            .scale(Vec3::new(2.0, 1.0, 1.0))
+            .rotate_y(90.0)
+            .translate(Vec3::new(0.0, 0.0, -5.0));
+
+        let ray = Ray::new(Point3::origin(), Vec3::new(0.0, 0.0, -1.0));
+        let rec = transform
+            .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+            .unwrap();
+        assert!((rec.t - 3.0).abs() < EPS, "{}", rec.t);
+
+        let bbox = transform.bounding_box().unwrap();
+        assert_close(bbox.min, Point3::new(-1.0, -1.0, -7.0));
+        assert_close(bbox.max, Point3::new(1.0, 1.0, -3.0));
+    }
+
+    #[test]
+    fn matrix() {
+        let matrix =
+            Mat4::translation(Vec3::new(1.0, 2.0, 3.0)) * Mat4::scaling(Vec3::new(2.0, 2.0, 2.0));
+        let transform = Transform::with_matrix(unit_sphere(), matrix).unwrap();
+
+        let bbox = transform.bounding_box().unwrap();
+        assert_close(bbox.min, Point3::new(-1.0, 0.0, 1.0));
+        assert_close(bbox.max, Point3::new(3.0, 4.0, 5.0));
+
+        let singular = Mat4::scaling(Vec3::new(1.0, 0.0, 1.0));
+        assert!(Transform::with_matrix(unit_sphere(), singular).is_none());
+    }
+
+    #[test]
+    fn normals_of_scaled_and_rotated_sphere() {
+        let center = Point3::new(1.0, 0.0, 0.0);
+        let transform = Transform::new(unit_sphere())
+            .scale(Vec3::new(1.0, 3.0, 0.5))
+            .rotate_x(30.0)
+            .translate(center - Point3::origin());
+
+        let (sin, cos) = 30_f64.to_radians().sin_cos();
+        // (p - center) rotated back by 30 degrees around the x axis
+        let to_object = |p: Point3| {
+            let d = p - center;
+            Vec3::new(d.x, cos * d.y + sin * d.z, cos * d.z - sin * d.y)
+        };
+
+        let hit = |origin: Point3, dir: Vec3| {
+            let ray = Ray::new(origin, dir);
+            transform
+                .hit(&ray, 0.001, f64::INFINITY, &mut test_rng())
+                .unwrap()
+        };
+
+        let dirs = [
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(-1.0, 0.2, 0.3),
+            Vec3::new(0.3, 0.8, 0.5),
+        ];
+
+        for dir in dirs {
+            let origin = center - 10.0 * dir + Vec3::new(0.2, 0.3, 0.1);
+            let rec = hit(origin, dir);
+
+            // the hit lies on the ellipsoid x^2 + y^2/9 + z^2/0.25 = 1 in object space
+            let q = to_object(rec.p);
+            assert!((q.x * q.x + q.y * q.y / 9.0 + q.z * q.z / 0.25 - 1.0).abs() < 1e-6);
+            assert!((rec.normal.len() - 1.0).abs() < EPS, "{:?}", rec.normal);
+
+            // the normal is the gradient of the implicit surface, rotated into world space
+            let g = Vec3::new(q.x, q.y / 9.0, q.z / 0.25);
+            let gradient = Vec3::new(g.x, cos * g.y - sin * g.z, sin * g.y + cos * g.z);
+            assert!(
+                (rec.normal - gradient.unit_vec()).len() < 1e-6,
+                "{:?}",
+                rec.normal
+            );
+
+            // chords to neighboring hits are tangent to the surface
+            for offset in [Vec3::new(1e-5, 0.0, 0.0), Vec3::new(0.0, 1e-5, 1e-5)] {
+                let chord = (hit(origin + offset, dir).p - rec.p).unit_vec();
+                assert!(rec.normal.dot(chord).abs() < 1e-3, "{:?}", chord);
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Triangles
+
+use rand::RngCore;
+
+use crate::lalg::{Point3, Vec3};
+use crate::ray::Ray;
+use crate::scene::material::Material;
+
+use super::aabb::Aabb;
+use super::{HitRecord, Hittable};
+
+/// A triangle object
+///
+/// The vertices are in counter-clockwise order when looking at the front face.
+#[derive(Debug, Clone)]
+pub struct Triangle {
+    pub vertices: [Point3; 3],
+    /// Optional per-vertex normals that are interpolated for smooth shading
+    pub normals: Option<[Vec3; 3]>,
+    pub mat_ptr: Box<dyn Material>,
+}
+
+impl Triangle {
+    /// Default shorthand constructor (for flat shading)
+    pub fn new(a: Point3, b: Point3, c: Point3, mat_ptr: Box<dyn Material>) -> Self {
+        Triangle {
+            vertices: [a, b, c],
+            normals: None,
+            mat_ptr,
+        }
+    }
+
+    /// Constructs a triangle with per-vertex normals (for smooth shading)
+    ///
+    /// Vertex normals that are (nearly) zero are replaced with the face normal.
+    pub fn with_normals(
+        vertices: [Point3; 3],
+        normals: [Vec3; 3],
+        mat_ptr: Box<dyn Material>,
+    ) -> Self {
+        let [a, b, c] = vertices;
+        let face_normal = (b - a).cross(c - a).unit_vec();
+
+        let normals = normals.map(|n| {
+            if n.near_zero() {
+                face_normal
+            } else {
+                n.unit_vec()
+            }
+        });
+
+        Triangle {
+            vertices,
+            normals: Some(normals),
+            mat_ptr,
+        }
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, _rng: &mut dyn RngCore) -> Option<HitRecord> {
+        // Möller-Trumbore intersection
+        let [a, b, c] = self.vertices;
+        let edge1 = b - a;
+        let edge2 = c - a;
+
+        let p_vec = ray.dir.cross(edge2);
+        let det = edge1.dot(p_vec);
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+
+        let t_vec = ray.origin - a;
+        let u = t_vec.dot(p_vec) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q_vec = t_vec.cross(edge1);
+        let v = ray.dir.dot(q_vec) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge2.dot(q_vec) * inv_det;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let outward_normal = match self.normals {
+            Some([na, nb, nc]) => ((1.0 - u - v) * na + u * nb + v * nc).unit_vec(),
+            None => edge1.cross(edge2).unit_vec(),
+        };
+
+        let p = ray.at(t);
+
+        let hit = HitRecord::new_with_face_normal(
+            p,
+            self.mat_ptr.clone(),
+            t,
+            (u, v),
+            ray,
+            outward_normal,
+        );
+
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        const PADDING: f64 = 0.0001;
+
+        let [a, b, c] = self.vertices;
+        let min = Point3::new(
+            a.x.min(b.x).min(c.x) - PADDING,
+            a.y.min(b.y).min(c.y) - PADDING,
+            a.z.min(b.z).min(c.z) - PADDING,
+        );
+        let max = Point3::new(
+            a.x.max(b.x).max(c.x) + PADDING,
+            a.y.max(b.y).max(c.y) + PADDING,
+            a.z.max(b.z).max(c.z) + PADDING,
+        );
+
+        Some(Aabb::new(min, max))
+    }
+}
@@ -1,3 +1,5 @@
+use rand::{Rng, RngCore};
+
 use crate::color::Color;
 use crate::ray::Ray;
 use crate::scene::hittable::HitRecord;
@@ -32,7 +34,12 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+    fn scatter(
+        &self,
+        ray_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<(Color, Ray)> {
         let attenuation = Color::white();
 
         let refraction_ratio = if rec.is_front_face {
@@ -47,14 +54,14 @@ impl Material for Dielectric {
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
 
         let direction = if cannot_refract
-            || Dielectric::reflectance(cos_theta, refraction_ratio) > rand::random()
+            || Dielectric::reflectance(cos_theta, refraction_ratio) > rng.gen::<f64>()
         {
             ray_in.dir.unit_vec().reflect(rec.normal)
         } else {
             ray_in.dir.unit_vec().refract(rec.normal, refraction_ratio)
         };
 
-        let scattered = Ray::new(rec.p, direction);
+        let scattered = Ray::with_time(rec.p, direction, ray_in.time);
 
         Some((attenuation, scattered))
     }
@@ -0,0 +1,44 @@
+use rand::RngCore;
+
+use crate::color::Color;
+use crate::ray::Ray;
+use crate::scene::hittable::HitRecord;
+
+use super::Material;
+
+/// Light emitting material that does not scatter any rays
+#[derive(Debug, Clone)]
+pub struct DiffuseLight {
+    pub emit: Color,
+}
+
+impl DiffuseLight {
+    /// Default shorthand constructor
+    pub fn new(emit: Color) -> Self {
+        DiffuseLight { emit }
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(
+        &self,
+        _ray_in: &Ray,
+        _rec: &HitRecord,
+        _rng: &mut dyn RngCore,
+    ) -> Option<(Color, Ray)> {
+        None
+    }
+
+    fn emitted(&self, _ray_in: &Ray, _rec: &HitRecord) -> Color {
+        self.emit
+    }
+
+    fn box_clone(&self) -> Box<dyn Material> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,50 @@
+use rand::RngCore;
+
+use crate::color::Color;
+use crate::lalg::Vec3;
+use crate::ray::Ray;
+use crate::scene::hittable::HitRecord;
+use crate::scene::texture::Texture;
+
+use super::Material;
+
+/// Phase function of participating media that scatters uniformly in all directions
+#[derive(Debug, Clone)]
+pub struct Isotropic {
+    pub albedo: Box<dyn Texture>,
+}
+
+impl Isotropic {
+    /// Default shorthand constructor (for a single color)
+    pub fn new(albedo: Color) -> Self {
+        Isotropic::textured(albedo.into())
+    }
+
+    /// Constructs a material whose color is given by a texture
+    pub fn textured(albedo: Box<dyn Texture>) -> Self {
+        Isotropic { albedo }
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(
+        &self,
+        ray_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<(Color, Ray)> {
+        let scattered = Ray::with_time(rec.p, Vec3::random_unit_vec(rng), ray_in.time);
+        let attenuation = self.albedo.value(rec.u, rec.v, rec.p);
+
+        Some((attenuation, scattered))
+    }
+
+    fn box_clone(&self) -> Box<dyn Material> {
+        Box::new(self.clone())
+    }
+}
@@ -1,18 +1,26 @@
+use rand::RngCore;
+
 use crate::color::Color;
 use crate::lalg::Vec3;
 use crate::ray::Ray;
 use crate::scene::hittable::HitRecord;
+use crate::scene::texture::Texture;
 
 use super::Material;
 
 #[derive(Debug, Clone)]
 pub struct Lambertian {
-    pub albedo: Color,
+    pub albedo: Box<dyn Texture>,
 }
 
 impl Lambertian {
-    /// Default shorthand constructor
+    /// Default shorthand constructor (for a single color)
     pub fn new(albedo: Color) -> Self {
+        Lambertian::textured(albedo.into())
+    }
+
+    /// Constructs a material whose color is given by a texture
+    pub fn textured(albedo: Box<dyn Texture>) -> Self {
         Lambertian { albedo }
     }
 
@@ -23,8 +31,13 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _ray_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
-        let scatter_dir = rec.normal + Vec3::random_unit_vec();
+    fn scatter(
+        &self,
+        ray_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<(Color, Ray)> {
+        let scatter_dir = rec.normal + Vec3::random_unit_vec(rng);
 
         let scatter_dir = if scatter_dir.near_zero() {
             rec.normal
@@ -32,8 +45,8 @@ impl Material for Lambertian {
             scatter_dir
         };
 
-        let scattered = Ray::new(rec.p, scatter_dir);
-        let attenuation = self.albedo;
+        let scattered = Ray::with_time(rec.p, scatter_dir, ray_in.time);
+        let attenuation = self.albedo.value(rec.u, rec.v, rec.p);
 
         Some((attenuation, scattered))
     }
@@ -1,19 +1,27 @@
+use rand::RngCore;
+
 use crate::color::Color;
 use crate::lalg::Vec3;
 use crate::ray::Ray;
 use crate::scene::hittable::HitRecord;
+use crate::scene::texture::Texture;
 
 use super::Material;
 
 #[derive(Debug, Clone)]
 pub struct Metal {
-    pub albedo: Color,
+    pub albedo: Box<dyn Texture>,
     pub fuzz: f64,
 }
 
 impl Metal {
-    /// Default shorthand constructor
+    /// Default shorthand constructor (for a single color)
     pub fn new(albedo: Color, fuzz: f64) -> Self {
+        Metal::textured(albedo.into(), fuzz)
+    }
+
+    /// Constructs a material whose color is given by a texture
+    pub fn textured(albedo: Box<dyn Texture>, fuzz: f64) -> Self {
         Metal {
             albedo,
             fuzz: fuzz.clamp(0.0, 1.0),
@@ -27,10 +35,19 @@ impl Metal {
 }
 
 impl Material for Metal {
-    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)> {
+    fn scatter(
+        &self,
+        ray_in: &Ray,
+        rec: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> Option<(Color, Ray)> {
         let reflected = ray_in.dir.unit_vec().reflect(rec.normal);
-        let scattered = Ray::new(rec.p, reflected + self.fuzz * Vec3::random_in_unit_sphere());
-        let attenuation = self.albedo;
+        let scattered = Ray::with_time(
+            rec.p,
+            reflected + self.fuzz * Vec3::random_in_unit_sphere(rng),
+            ray_in.time,
+        );
+        let attenuation = self.albedo.value(rec.u, rec.v, rec.p);
 
         if scattered.dir.dot(rec.normal) > 0.0 {
             Some((attenuation, scattered))
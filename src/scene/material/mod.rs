@@ -2,22 +2,36 @@
 
 use std::fmt::Debug;
 
+use rand::RngCore;
+
 use crate::color::Color;
 use crate::ray::Ray;
 use crate::scene::hittable::HitRecord;
 
 mod dielectric;
+mod diffuse_light;
+mod isotropic;
 mod lambertian;
 mod metal;
 
 pub use dielectric::Dielectric;
+pub use diffuse_light::DiffuseLight;
+pub use isotropic::Isotropic;
 pub use lambertian::Lambertian;
 pub use metal::Metal;
 
 /// Material that defines how an object interacts with its environment
 pub trait Material: Debug + Send + Sync {
     /// Provide the way how the material handles incoming rays
-    fn scatter(&self, ray_in: &Ray, rec: &HitRecord) -> Option<(Color, Ray)>;
+    ///
+    /// All randomness must come from `rng`, so renders are reproducible.
+    fn scatter(&self, ray_in: &Ray, rec: &HitRecord, rng: &mut dyn RngCore)
+        -> Option<(Color, Ray)>;
+
+    /// Provide the light that the material emits (black by default)
+    fn emitted(&self, _ray_in: &Ray, _rec: &HitRecord) -> Color {
+        Color::black()
+    }
 
     /// Provide a method for cloning as trait object
     fn box_clone(&self) -> Box<dyn Material>;
@@ -41,7 +55,12 @@ impl DefaultMaterial {
 }
 
 impl Material for DefaultMaterial {
-    fn scatter(&self, _ray_in: &Ray, _rec: &HitRecord) -> Option<(Color, Ray)> {
+    fn scatter(
+        &self,
+        _ray_in: &Ray,
+        _rec: &HitRecord,
+        _rng: &mut dyn RngCore,
+    ) -> Option<(Color, Ray)> {
         None
     }
 
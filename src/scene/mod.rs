@@ -1,10 +1,15 @@
 //! The virtual scene of objects
 
+use crate::camera::Camera;
 use crate::color::Color;
-use hittable::{Hittable, HittableList};
+use hittable::{BvhNode, Hittable, HittableList};
 
+mod file;
 pub mod hittable;
 pub mod material;
+pub mod texture;
+
+pub use file::SceneFileError;
 
 /// Background of a scene
 #[derive(Debug, Clone)]
@@ -19,6 +24,8 @@ pub enum SceneBackground {
 pub struct Scene {
     pub objects: HittableList,
     pub background: SceneBackground,
+    /// Initial camera suggested by the scene
+    pub camera: Option<Camera>,
 }
 
 impl Scene {
@@ -27,6 +34,7 @@ impl Scene {
         Scene {
             objects,
             background,
+            camera: None,
         }
     }
 
@@ -35,6 +43,8 @@ impl Scene {
         SceneBuilder {
             objects: HittableList::empty(),
             background,
+            camera: None,
+            use_bvh: false,
         }
     }
 }
@@ -43,6 +53,8 @@ impl Scene {
 pub struct SceneBuilder {
     objects: HittableList,
     background: SceneBackground,
+    camera: Option<Camera>,
+    use_bvh: bool,
 }
 
 impl SceneBuilder {
@@ -52,8 +64,38 @@ impl SceneBuilder {
         self
     }
 
+    /// Replace the background of the later [Scene]
+    pub fn background(mut self, background: SceneBackground) -> SceneBuilder {
+        self.background = background;
+        self
+    }
+
+    /// Set the initial camera of the later [Scene]
+    pub fn camera(mut self, camera: Camera) -> SceneBuilder {
+        self.camera = Some(camera);
+        self
+    }
+
+    /// Put the objects into a [bounding volume hierarchy][BvhNode] when building
+    ///
+    /// This speeds up rendering of scenes with many objects.
+    pub fn use_bvh(mut self, use_bvh: bool) -> SceneBuilder {
+        self.use_bvh = use_bvh;
+        self
+    }
+
     /// Build the final scene
     pub fn build(self) -> Scene {
-        Scene::new(self.objects, self.background)
+        let objects = if self.use_bvh {
+            HittableList::new(vec![BvhNode::from(self.objects).boxed()])
+        } else {
+            self.objects
+        };
+
+        Scene {
+            objects,
+            background: self.background,
+            camera: self.camera,
+        }
     }
 }
@@ -0,0 +1,97 @@
+use crate::color::Color;
+use crate::lalg::Point3;
+
+use super::Texture;
+
+/// Three-dimensional checker pattern of two alternating textures
+///
+/// The pattern is computed from the hit point, so it does not depend on the surface coordinates.
+#[derive(Debug, Clone)]
+pub struct CheckerTexture {
+    pub even: Box<dyn Texture>,
+    pub odd: Box<dyn Texture>,
+    /// Edge length of a single cell
+    pub scale: f64,
+}
+
+impl CheckerTexture {
+    /// Default shorthand constructor
+    pub fn new(even: Box<dyn Texture>, odd: Box<dyn Texture>, scale: f64) -> Self {
+        CheckerTexture { even, odd, scale }
+    }
+
+    /// Constructs a checker pattern of two colors
+    pub fn from_colors(even: Color, odd: Color, scale: f64) -> Self {
+        CheckerTexture::new(even.into(), odd.into(), scale)
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color {
+        let cell = |x: f64| (x / self.scale).floor() as i64;
+
+        if (cell(p.x) + cell(p.y) + cell(p.z)).rem_euclid(2) == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn Texture> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CheckerTexture;
+    use crate::color::Color;
+    use crate::lalg::Point3;
+    use crate::scene::texture::Texture;
+
+    #[test]
+    fn parity() {
+        let (even, odd) = (Color::white(), Color::black());
+        let checker = CheckerTexture::from_colors(even, odd, 0.5);
+
+        let cases = [
+            ((0.1, 0.1, 0.1), even),
+            ((0.6, 0.1, 0.1), odd),
+            ((0.1, 0.6, 0.1), odd),
+            ((0.1, 0.1, 0.6), odd),
+            ((0.6, 0.6, 0.1), even),
+            ((0.6, 0.6, 0.6), odd),
+            // cells below zero continue the pattern
+            ((-0.1, 0.1, 0.1), odd),
+            ((-0.6, 0.1, 0.1), even),
+            ((-0.1, -0.1, -0.1), odd),
+            // the lower boundary belongs to a cell
+            ((0.5, 0.0, 0.0), odd),
+            ((1.0, 0.0, 0.0), even),
+        ];
+
+        for ((x, y, z), expected) in cases {
+            assert_eq!(
+                checker.value(0.0, 0.0, Point3::new(x, y, z)),
+                expected,
+                "({}, {}, {})",
+                x,
+                y,
+                z
+            );
+        }
+    }
+
+    #[test]
+    fn independent_of_surface_coordinates() {
+        let checker = CheckerTexture::from_colors(Color::white(), Color::black(), 1.0);
+        let p = Point3::new(0.5, 1.5, 2.5);
+
+        assert_eq!(checker.value(0.0, 0.0, p), checker.value(0.9, 0.3, p));
+    }
+}
@@ -0,0 +1,127 @@
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::color::Color;
+use crate::lalg::Point3;
+use crate::render::image::load_image;
+use crate::render::FrameBuffer;
+
+use super::Texture;
+
+/// Texture that maps an image onto the surface coordinates
+#[derive(Debug, Clone)]
+pub struct ImageTexture {
+    image: Arc<FrameBuffer>,
+}
+
+impl ImageTexture {
+    /// Default shorthand constructor
+    pub fn new(image: FrameBuffer) -> Self {
+        ImageTexture {
+            image: Arc::new(image),
+        }
+    }
+
+    /// Load the texture from an image file
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(ImageTexture::new(load_image(path)?))
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: Point3) -> Color {
+        let (width, height) = (self.image.width(), self.image.height());
+        if width == 0 || height == 0 {
+            // cyan as a debugging aid
+            return Color::new(0.0, 1.0, 1.0);
+        }
+
+        // flip v because images start at the top
+        let u = u.clamp(0.0, 1.0);
+        let v = 1.0 - v.clamp(0.0, 1.0);
+
+        let x = ((u * width as f64) as usize).min(width - 1);
+        let y = ((v * height as f64) as usize).min(height - 1);
+
+        self.image.get(x, y)
+    }
+
+    fn box_clone(&self) -> Box<dyn Texture> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ImageTexture;
+    use crate::color::Color;
+    use crate::lalg::Point3;
+    use crate::render::FrameBuffer;
+    use crate::scene::texture::Texture;
+
+    /// 4x2 image whose red channel is the column and green channel the row (from the top)
+    fn texture() -> ImageTexture {
+        let pixels = (0..8)
+            .map(|i| Color::new((i % 4) as f64, (i / 4) as f64, 0.0))
+            .collect();
+
+        ImageTexture::new(FrameBuffer::from_pixels(4, 2, pixels))
+    }
+
+    /// Return the column and row of the texel at `u`, `v`
+    fn texel(u: f64, v: f64) -> (f64, f64) {
+        let color = texture().value(u, v, Point3::origin());
+        (color.r, color.g)
+    }
+
+    #[test]
+    fn uv_to_texel() {
+        let cases = [
+            // v points up, the image starts at the top
+            ((0.1, 0.9), (0.0, 0.0)),
+            ((0.1, 0.1), (0.0, 1.0)),
+            ((0.3, 0.9), (1.0, 0.0)),
+            ((0.6, 0.6), (2.0, 0.0)),
+            ((0.9, 0.4), (3.0, 1.0)),
+            // texel boundaries
+            ((0.25, 0.9), (1.0, 0.0)),
+            ((0.1, 0.5), (0.0, 1.0)),
+        ];
+
+        for ((u, v), expected) in cases {
+            assert_eq!(texel(u, v), expected, "u = {}, v = {}", u, v);
+        }
+    }
+
+    #[test]
+    fn clamps_coordinates() {
+        let cases = [
+            ((0.0, 0.0), (0.0, 1.0)),
+            ((1.0, 1.0), (3.0, 0.0)),
+            ((0.0, 1.0), (0.0, 0.0)),
+            ((1.0, 0.0), (3.0, 1.0)),
+            ((-0.5, 2.0), (0.0, 0.0)),
+            ((1.5, -1.0), (3.0, 1.0)),
+        ];
+
+        for ((u, v), expected) in cases {
+            assert_eq!(texel(u, v), expected, "u = {}, v = {}", u, v);
+        }
+    }
+
+    #[test]
+    fn empty_image() {
+        let texture = ImageTexture::new(FrameBuffer::new(0, 0));
+
+        assert_eq!(
+            texture.value(0.5, 0.5, Point3::origin()),
+            Color::new(0.0, 1.0, 1.0)
+        );
+    }
+}
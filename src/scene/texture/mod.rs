@@ -0,0 +1,39 @@
+//! Surface textures
+
+use std::fmt::Debug;
+
+use crate::color::Color;
+use crate::lalg::Point3;
+
+mod checker;
+mod image;
+mod noise;
+mod perlin;
+mod solid;
+
+pub use self::image::ImageTexture;
+pub use checker::CheckerTexture;
+pub use noise::{MarbleTexture, NoiseTexture, WoodTexture};
+pub use perlin::Perlin;
+pub use solid::SolidColor;
+
+/// Texture that defines the color of a surface at a specific point
+pub trait Texture: Debug + Send + Sync {
+    /// Return the color at the surface coordinates `u`, `v` of the hit point `p`
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color;
+
+    /// Provide a method for cloning as trait object
+    fn box_clone(&self) -> Box<dyn Texture>;
+}
+
+impl Clone for Box<dyn Texture> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+impl From<Color> for Box<dyn Texture> {
+    fn from(color: Color) -> Self {
+        SolidColor::new(color).boxed()
+    }
+}
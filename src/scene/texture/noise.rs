@@ -0,0 +1,226 @@
+//! Procedural solid textures based on [Perlin] noise
+
+use std::sync::Arc;
+
+use crate::color::Color;
+use crate::lalg::Point3;
+
+use super::perlin::Perlin;
+use super::Texture;
+
+/// Number of noise octaves that are summed up for turbulence
+const TURBULENCE_DEPTH: usize = 7;
+
+/// Turbulent noise that blends between two colors
+#[derive(Debug, Clone)]
+pub struct NoiseTexture {
+    noise: Arc<Perlin>,
+    pub scale: f64,
+    pub low: Color,
+    pub high: Color,
+}
+
+impl NoiseTexture {
+    /// Default shorthand constructor (black and white)
+    pub fn new(seed: u64, scale: f64) -> Self {
+        NoiseTexture::with_colors(seed, scale, Color::black(), Color::white())
+    }
+
+    /// Constructs noise that blends between the colors `low` and `high`
+    pub fn with_colors(seed: u64, scale: f64, low: Color, high: Color) -> Self {
+        NoiseTexture {
+            noise: Arc::new(Perlin::new(seed)),
+            scale,
+            low,
+            high,
+        }
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        let t = self.noise.turb(self.scale * p, TURBULENCE_DEPTH);
+        lerp(self.low, self.high, t)
+    }
+
+    fn box_clone(&self) -> Box<dyn Texture> {
+        Box::new(self.clone())
+    }
+}
+
+/// Marble veins along the z axis, distorted by turbulence
+#[derive(Debug, Clone)]
+pub struct MarbleTexture {
+    noise: Arc<Perlin>,
+    pub scale: f64,
+    pub vein: Color,
+    pub base: Color,
+}
+
+impl MarbleTexture {
+    /// Default shorthand constructor (dark veins in white stone)
+    pub fn new(seed: u64, scale: f64) -> Self {
+        MarbleTexture::with_colors(seed, scale, Color::new(0.1, 0.1, 0.1), Color::white())
+    }
+
+    /// Constructs marble with veins of color `vein` in stone of color `base`
+    pub fn with_colors(seed: u64, scale: f64, vein: Color, base: Color) -> Self {
+        MarbleTexture {
+            noise: Arc::new(Perlin::new(seed)),
+            scale,
+            vein,
+            base,
+        }
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl Texture for MarbleTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        let turb = self.noise.turb(p, TURBULENCE_DEPTH);
+        let t = 0.5 * (1.0 + (self.scale * p.z + 10.0 * turb).sin());
+
+        lerp(self.vein, self.base, t)
+    }
+
+    fn box_clone(&self) -> Box<dyn Texture> {
+        Box::new(self.clone())
+    }
+}
+
+/// Wood with growth rings around the y axis, distorted by turbulence
+#[derive(Debug, Clone)]
+pub struct WoodTexture {
+    noise: Arc<Perlin>,
+    pub scale: f64,
+    pub ring: Color,
+    pub base: Color,
+}
+
+impl WoodTexture {
+    /// Default shorthand constructor (brown)
+    pub fn new(seed: u64, scale: f64) -> Self {
+        WoodTexture::with_colors(
+            seed,
+            scale,
+            Color::new(0.35, 0.18, 0.07),
+            Color::new(0.75, 0.52, 0.3),
+        )
+    }
+
+    /// Constructs wood with rings of color `ring` in wood of color `base`
+    pub fn with_colors(seed: u64, scale: f64, ring: Color, base: Color) -> Self {
+        WoodTexture {
+            noise: Arc::new(Perlin::new(seed)),
+            scale,
+            ring,
+            base,
+        }
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl Texture for WoodTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        let dist = (p.x.powi(2) + p.z.powi(2)).sqrt();
+        let turb = self.noise.turb(p, TURBULENCE_DEPTH);
+        let rings = self.scale * dist + 2.0 * turb;
+        let t = rings - rings.floor();
+
+        lerp(self.base, self.ring, t)
+    }
+
+    fn box_clone(&self) -> Box<dyn Texture> {
+        Box::new(self.clone())
+    }
+}
+
+/// Linear interpolation between two colors
+fn lerp(a: Color, b: Color, t: f64) -> Color {
+    (1.0 - t) * a + t * b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MarbleTexture, NoiseTexture, WoodTexture};
+    use crate::color::Color;
+    use crate::lalg::Point3;
+    use crate::scene::texture::Texture;
+
+    /// Points on a grid of 10 units around the origin
+    fn points() -> impl Iterator<Item = Point3> {
+        (0..1000).map(|i| {
+            let coord = |n: usize| (n % 10) as f64 * 1.37 - 6.1;
+            Point3::new(coord(i), coord(i / 10), coord(i / 100))
+        })
+    }
+
+    fn assert_in_unit_range(texture: &dyn Texture) {
+        for p in points() {
+            let Color { r, g, b } = texture.value(0.0, 0.0, p);
+
+            for channel in [r, g, b] {
+                assert!((0.0..=1.0).contains(&channel), "{} at {:?}", channel, p);
+            }
+        }
+    }
+
+    fn assert_same(a: &dyn Texture, b: &dyn Texture) {
+        for p in points() {
+            assert_eq!(a.value(0.0, 0.0, p), b.value(0.0, 0.0, p));
+        }
+    }
+
+    fn assert_differ(a: &dyn Texture, b: &dyn Texture) {
+        assert!(points().any(|p| a.value(0.0, 0.0, p) != b.value(0.0, 0.0, p)));
+    }
+
+    #[test]
+    fn seeded() {
+        assert_same(&NoiseTexture::new(1, 4.0), &NoiseTexture::new(1, 4.0));
+        assert_same(&MarbleTexture::new(1, 4.0), &MarbleTexture::new(1, 4.0));
+        assert_same(&WoodTexture::new(1, 4.0), &WoodTexture::new(1, 4.0));
+
+        assert_differ(&NoiseTexture::new(1, 4.0), &NoiseTexture::new(2, 4.0));
+        assert_differ(&MarbleTexture::new(1, 4.0), &MarbleTexture::new(2, 4.0));
+        assert_differ(&WoodTexture::new(1, 4.0), &WoodTexture::new(2, 4.0));
+    }
+
+    #[test]
+    fn values_in_unit_range() {
+        let (black, white) = (Color::black(), Color::white());
+
+        for seed in 0..3 {
+            assert_in_unit_range(&NoiseTexture::new(seed, 4.0));
+            assert_in_unit_range(&MarbleTexture::with_colors(seed, 4.0, black, white));
+            assert_in_unit_range(&WoodTexture::with_colors(seed, 4.0, black, white));
+        }
+    }
+
+    #[test]
+    fn blends_between_colors() {
+        let (low, high) = (Color::new(0.2, 0.4, 0.0), Color::new(0.6, 0.4, 1.0));
+        let marble = MarbleTexture::with_colors(5, 2.0, low, high);
+
+        for p in points() {
+            let color = marble.value(0.0, 0.0, p);
+            assert!((0.2..=0.6).contains(&color.r));
+            assert!((color.g - 0.4).abs() < 1e-12);
+            // the channels move together
+            assert!(((color.r - 0.2) / 0.4 - color.b).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Perlin noise
+
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::lalg::{Point3, Vec3};
+
+const POINT_COUNT: usize = 256;
+
+/// Perlin noise generator that is deterministic for a given seed
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    ranvec: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    /// Default shorthand constructor
+    pub fn new(seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+        let ranvec = (0..POINT_COUNT)
+            .map(|_| {
+                Vec3::new(
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                )
+                .unit_vec()
+            })
+            .collect();
+
+        let mut perm = || {
+            let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+            p.shuffle(&mut rng);
+            p
+        };
+
+        Perlin {
+            perm_x: perm(),
+            perm_y: perm(),
+            perm_z: perm(),
+            ranvec,
+        }
+    }
+
+    /// Return smooth noise in `[-1; 1]` at a point
+    pub fn noise(&self, p: Point3) -> f64 {
+        let (u, v, w) = (p.x - p.x.floor(), p.y - p.y.floor(), p.z - p.z.floor());
+        let (i, j, k) = (p.x.floor() as i64, p.y.floor() as i64, p.z.floor() as i64);
+
+        let mut c = [[[Vec3::origin(); 2]; 2]; 2];
+        for (di, plane) in c.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, corner) in row.iter_mut().enumerate() {
+                    let idx = self.perm_x[wrap(i + di as i64)]
+                        ^ self.perm_y[wrap(j + dj as i64)]
+                        ^ self.perm_z[wrap(k + dk as i64)];
+                    *corner = self.ranvec[idx];
+                }
+            }
+        }
+
+        perlin_interp(&c, u, v, w)
+    }
+
+    /// Return turbulence in `[0; 1)` (sum of `depth` octaves of noise) at a point
+    pub fn turb(&self, p: Point3, depth: usize) -> f64 {
+        let mut accum = 0.0;
+        let mut p = p;
+        let mut weight = 1.0;
+
+        for _ in 0..depth {
+            accum += weight * self.noise(p);
+            weight *= 0.5;
+            p *= 2.0;
+        }
+
+        accum.abs().min(1.0)
+    }
+}
+
+fn wrap(i: i64) -> usize {
+    i.rem_euclid(POINT_COUNT as i64) as usize
+}
+
+/// Trilinear interpolation of the gradients with hermite smoothing
+fn perlin_interp(c: &[[[Vec3; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
+    let uu = u * u * (3.0 - 2.0 * u);
+    let vv = v * v * (3.0 - 2.0 * v);
+    let ww = w * w * (3.0 - 2.0 * w);
+
+    let mut accum = 0.0;
+    for (i, plane) in c.iter().enumerate() {
+        for (j, row) in plane.iter().enumerate() {
+            for (k, &grad) in row.iter().enumerate() {
+                let (fi, fj, fk) = (i as f64, j as f64, k as f64);
+                let weight = Vec3::new(u - fi, v - fj, w - fk);
+
+                accum += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                    * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                    * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                    * grad.dot(weight);
+            }
+        }
+    }
+
+    accum
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::Perlin;
+    use crate::lalg::Point3;
+
+    /// Random points in a cube of 20 units around the origin, the same on every run
+    fn points() -> Vec<Point3> {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+
+        (0..500)
+            .map(|_| {
+                Point3::new(
+                    rng.gen_range(-10.0..10.0),
+                    rng.gen_range(-10.0..10.0),
+                    rng.gen_range(-10.0..10.0),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_same_noise() {
+        let (a, b) = (Perlin::new(3), Perlin::new(3));
+
+        for p in points() {
+            assert_eq!(a.noise(p), b.noise(p));
+            assert_eq!(a.turb(p, 7), b.turb(p, 7));
+        }
+    }
+
+    #[test]
+    fn different_seeds_differ() {
+        let (a, b) = (Perlin::new(3), Perlin::new(4));
+
+        let differing = points()
+            .into_iter()
+            .filter(|&p| a.noise(p) != b.noise(p))
+            .count();
+
+        assert!(differing > 450, "only {} of 500 points differ", differing);
+    }
+
+    #[test]
+    fn noise_range() {
+        let perlin = Perlin::new(0);
+
+        for p in points() {
+            let noise = perlin.noise(p);
+            assert!((-1.0..=1.0).contains(&noise), "{} at {:?}", noise, p);
+
+            let turb = perlin.turb(p, 7);
+            assert!((0.0..=1.0).contains(&turb), "{} at {:?}", turb, p);
+        }
+    }
+
+    #[test]
+    fn zero_at_lattice_points() {
+        let perlin = Perlin::new(0);
+
+        for (x, y, z) in [(0.0, 0.0, 0.0), (1.0, -2.0, 3.0), (-7.0, 5.0, 300.0)] {
+            assert!(perlin.noise(Point3::new(x, y, z)).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::color::Color;
+use crate::lalg::Point3;
+
+use super::Texture;
+
+/// Texture of a single color
+#[derive(Debug, Clone)]
+pub struct SolidColor {
+    pub color: Color,
+}
+
+impl SolidColor {
+    /// Default shorthand constructor
+    pub fn new(color: Color) -> Self {
+        SolidColor { color }
+    }
+
+    /// Wrap in a `Box`
+    pub fn boxed(self) -> Box<Self> {
+        Box::new(self)
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: Point3) -> Color {
+        self.color
+    }
+
+    fn box_clone(&self) -> Box<dyn Texture> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SolidColor;
+    use crate::color::Color;
+    use crate::lalg::Point3;
+    use crate::scene::texture::Texture;
+
+    #[test]
+    fn same_color_everywhere() {
+        let color = Color::new(0.1, 0.2, 0.3);
+        let solid = SolidColor::new(color);
+
+        assert_eq!(solid.value(0.0, 0.0, Point3::origin()), color);
+        assert_eq!(solid.value(1.0, 0.5, Point3::new(-3.0, 2.0, 9.0)), color);
+    }
+}
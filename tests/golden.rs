@@ -0,0 +1,234 @@
+//! Golden-image regression tests
+//!
+//! Canonical scenes are rendered with a fixed seed to a grid of characters and to a color buffer,
+//! which are compared with the files in `tests/golden`. Renders are reproducible,
+//! so only the last bits of the floating point math of another platform are tolerated.
+//!
+//! After an intended change of the output, regenerate the files with
+//! `UPDATE_GOLDEN=1 cargo test --test golden` and review the difference.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use raytrascii::render::image::{load_image, read_ppm, save_image, write_ppm};
+use raytrascii::render::{
+    render_to_buffer, render_to_text, FrameBuffer, OutputSettings, Palette, RenderMode,
+    RenderSettings,
+};
+use raytrascii::scene::Scene;
+
+/// Size of the character grid
+const COLS: u16 = 48;
+const ROWS: u16 = 16;
+
+/// Size of the color buffer
+const WIDTH: usize = 32;
+const HEIGHT: usize = 24;
+
+const MAX_DEPTH: usize = 8;
+const SEED: u64 = 42;
+
+/// Largest share of characters that may differ from the golden file
+const TEXT_TOLERANCE: f64 = 0.005;
+
+/// Largest difference of a color channel (in 8-bit values) that counts as equal
+const CHANNEL_TOLERANCE: u8 = 2;
+
+/// Largest share of pixels that may differ from the golden file
+const IMAGE_TOLERANCE: f64 = 0.005;
+
+const SPHERES: &str = "
+background vertical top=0.5,0.7,1.0 bottom=1,1,1
+camera pos=0,0.5,1 lookat=0,0,-1 vfov=60
+material ground lambertian albedo=0.8,0.8,0.0
+material red lambertian albedo=0.7,0.3,0.3
+material blue lambertian albedo=0.1,0.2,0.5
+sphere center=0,-100.5,-1 radius=100 material=ground
+sphere center=-0.6,0,-1 radius=0.5 material=red
+sphere center=0.6,0,-1.2 radius=0.5 material=blue
+";
+
+const RECTS: &str = "
+background solid color=0.8,0.8,0.8
+camera pos=0.5,0.5,-1.44 lookat=0.5,0.5,0 vfov=40
+material red lambertian albedo=0.65,0.05,0.05
+material white lambertian albedo=0.73,0.73,0.73
+material green lambertian albedo=0.12,0.45,0.15
+material lamp light emit=4,4,4
+rect plane=yz y=0,1 z=0,1 k=1 material=green
+rect plane=yz y=0,1 z=0,1 k=0 material=red
+rect plane=xz x=0,1 z=0,1 k=0 material=white
+rect plane=xz x=0,1 z=0,1 k=1 material=white
+rect plane=xy x=0,1 y=0,1 k=1 material=white
+rect plane=xz x=0.3,0.7 z=0.3,0.7 k=0.998 material=lamp
+";
+
+const DIELECTRIC: &str = "
+background vertical top=0.5,0.7,1.0 bottom=1,1,1
+camera pos=0,0.5,1 lookat=0,0,-1 vfov=60
+material checks lambertian albedo=0.3,0.6,0.3
+material glass dielectric ir=1.5
+sphere center=0,-100.5,-1 radius=100 material=checks
+sphere center=0,0,-1 radius=0.5 material=glass
+sphere center=0,0,-1 radius=-0.4 material=glass
+";
+
+const METAL: &str = "
+background vertical top=0.5,0.7,1.0 bottom=1,1,1
+camera pos=0,0.5,1 lookat=0,0,-1 vfov=60
+material ground lambertian albedo=0.5,0.5,0.5
+material mirror metal albedo=0.8,0.8,0.8 fuzz=0
+material brushed metal albedo=0.8,0.6,0.2 fuzz=0.5
+sphere center=0,-100.5,-1 radius=100 material=ground
+sphere center=-0.6,0,-1 radius=0.5 material=mirror
+sphere center=0.6,0,-1 radius=0.5 material=brushed
+";
+
+#[test]
+fn spheres() {
+    check_scene("spheres", SPHERES, 64);
+}
+
+#[test]
+fn rects() {
+    // indirect light needs more samples to converge
+    check_scene("rects", RECTS, 256);
+}
+
+#[test]
+fn dielectric() {
+    check_scene("dielectric", DIELECTRIC, 64);
+}
+
+#[test]
+fn metal() {
+    check_scene("metal", METAL, 64);
+}
+
+/// Render a scene to text and to a color buffer and compare both with the golden files
+fn check_scene(name: &str, source: &str, samples_per_pixel: usize) {
+    let scene: Scene = source.parse().expect("Invalid test scene");
+    let cam = scene.camera.clone().unwrap_or_default();
+
+    let settings = RenderSettings {
+        seed: SEED,
+        ..RenderSettings::new(MAX_DEPTH, samples_per_pixel)
+    };
+    let output = OutputSettings::new(RenderMode::Brightness, Palette::default());
+
+    let text = render_to_text(&scene, &cam, COLS, ROWS, &settings, &output);
+    check_text(name, &text);
+
+    let buffer = render_to_buffer(&scene, &cam, WIDTH, HEIGHT, &settings);
+    check_image(name, &buffer);
+}
+
+fn golden_path(file: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(file)
+}
+
+fn update_golden() -> bool {
+    env::var_os("UPDATE_GOLDEN").is_some()
+}
+
+fn check_text(name: &str, actual: &str) {
+    let path = golden_path(&format!("{}.txt", name));
+
+    if update_golden() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, format!("{}\n", actual)).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!(
+            "Could not read {} ({}), run with UPDATE_GOLDEN=1 to create it",
+            path.display(),
+            err
+        )
+    });
+    let expected = expected.trim_end_matches('\n');
+
+    let expected_lines: Vec<Vec<char>> = expected.lines().map(|l| l.chars().collect()).collect();
+    let actual_lines: Vec<Vec<char>> = actual.lines().map(|l| l.chars().collect()).collect();
+
+    assert_eq!(
+        expected_lines.iter().map(Vec::len).collect::<Vec<_>>(),
+        actual_lines.iter().map(Vec::len).collect::<Vec<_>>(),
+        "{}: size of the text differs from the golden file",
+        name
+    );
+
+    let total = actual_lines.iter().map(Vec::len).sum::<usize>();
+    let differing = expected_lines
+        .iter()
+        .flatten()
+        .zip(actual_lines.iter().flatten())
+        .filter(|(e, a)| e != a)
+        .count();
+
+    assert!(
+        differing as f64 <= TEXT_TOLERANCE * total as f64,
+        "{}: {} of {} characters differ from the golden file\n\nexpected:\n{}\n\nactual:\n{}",
+        name,
+        differing,
+        total,
+        expected,
+        actual
+    );
+}
+
+fn check_image(name: &str, actual: &FrameBuffer) {
+    let path = golden_path(&format!("{}.ppm", name));
+
+    if update_golden() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        save_image(actual, &path).unwrap();
+        return;
+    }
+
+    let expected = load_image(&path).unwrap_or_else(|err| {
+        panic!(
+            "Could not read {} ({}), run with UPDATE_GOLDEN=1 to create it",
+            path.display(),
+            err
+        )
+    });
+
+    // quantize like the golden file
+    let mut data = Vec::new();
+    write_ppm(actual, &mut data).unwrap();
+    let actual = read_ppm(&data).unwrap();
+
+    assert_eq!(
+        (expected.width(), expected.height()),
+        (actual.width(), actual.height()),
+        "{}: size of the image differs from the golden file",
+        name
+    );
+
+    let to_rgb8 = |c: f64| (c.sqrt().clamp(0.0, 1.0) * 255.0).round() as i32;
+    let differing = expected
+        .pixels()
+        .iter()
+        .zip(actual.pixels())
+        .filter(|(e, a)| {
+            [(e.r, a.r), (e.g, a.g), (e.b, a.b)]
+                .iter()
+                .any(|&(e, a)| (to_rgb8(e) - to_rgb8(a)).abs() > CHANNEL_TOLERANCE as i32)
+        })
+        .count();
+
+    let total = actual.pixels().len();
+    assert!(
+        differing as f64 <= IMAGE_TOLERANCE * total as f64,
+        "{}: {} of {} pixels differ from the golden file",
+        name,
+        differing,
+        total
+    );
+}
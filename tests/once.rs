@@ -0,0 +1,63 @@
+//! One-shot output of the binary (`--once`) when it is piped
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Render a bundled scene once and return the printed text
+fn render_once(scene: &str, mode: &str) -> String {
+    let scene = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("scenes")
+        .join(scene);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_raytrascii"))
+        .arg(scene)
+        .args(["--once", "--mode", mode, "--size", "32x12"])
+        .args(["--samples", "4", "--depth", "4"])
+        .output()
+        .expect("Could not run the binary");
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).expect("Output is not UTF-8")
+}
+
+fn assert_scenes_differ(mode: &str) {
+    let default = render_once("default.scene", mode);
+    let cornell = render_once("cornell.scene", mode);
+
+    // piped output has no escape sequences
+    assert!(!default.contains('\x1b'), "{}", default);
+    assert_eq!(default.lines().count(), 12);
+    assert_ne!(default, cornell, "{}: scenes look the same", mode);
+}
+
+#[test]
+fn color_mode_differs_between_scenes() {
+    assert_scenes_differ("color");
+}
+
+#[test]
+fn half_block_mode_differs_between_scenes() {
+    assert_scenes_differ("half-block");
+}
+
+#[test]
+fn brightness_mode_differs_between_scenes() {
+    assert_scenes_differ("brightness");
+}
+
+#[test]
+fn rejects_nan_camera_settings() {
+    for option in ["--vfov", "--aperture", "--focus-dist"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_raytrascii"))
+            .args(["--once", "--size", "4x2", option, "NaN"])
+            .output()
+            .expect("Could not run the binary");
+
+        assert!(!output.status.success(), "{} NaN was accepted", option);
+    }
+}